@@ -0,0 +1,101 @@
+use alloy_primitives::{Address, U8};
+use stylus_sdk::{evm, stylus_proc::sol_storage};
+use crate::constants::{MASTER_ROLE, PAUSER_ROLE};
+use crate::errors::{BResult, BitsaveErrors, InvalidAddress, InvalidRole, Unauthorized};
+use crate::events::{MasterTransferProposed, MasterTransferred, RoleGranted, RoleRevoked};
+
+sol_storage! {
+    pub struct AccessControl {
+        address master_address;
+        address pending_master;
+        mapping(uint8 => mapping(address => bool)) roles;
+    }
+}
+
+impl AccessControl {
+    pub fn init(&mut self, master: Address) {
+        self.master_address.set(master);
+    }
+
+    pub fn master(&self) -> Address {
+        self.master_address.get()
+    }
+
+    pub fn pending_master(&self) -> Address {
+        self.pending_master.get()
+    }
+
+    /// Master holds every role
+    pub fn has_role(&self, role: u8, account: Address) -> bool {
+        if account == self.master_address.get() {
+            return true;
+        }
+        role != MASTER_ROLE && self.roles.get(U8::from(role)).get(account)
+    }
+
+    /// Guard for role restricted methods
+    pub fn only_role(&self, role: u8, account: Address) -> BResult<()> {
+        if !self.has_role(role, account) {
+            return Err(BitsaveErrors::Unauthorized(Unauthorized { caller: account }));
+        }
+        Ok(())
+    }
+
+    pub fn grant_role(&mut self, role: u8, account: Address, sender: Address) -> BResult<()> {
+        self.only_role(MASTER_ROLE, sender)?;
+        Self::check_role(role)?;
+        if account == Address::ZERO {
+            return Err(BitsaveErrors::InvalidAddress(InvalidAddress {}));
+        }
+
+        self.roles.setter(U8::from(role)).insert(account, true);
+        evm::log(RoleGranted { role, account, sender });
+        Ok(())
+    }
+
+    pub fn revoke_role(&mut self, role: u8, account: Address, sender: Address) -> BResult<()> {
+        self.only_role(MASTER_ROLE, sender)?;
+        Self::check_role(role)?;
+
+        self.roles.setter(U8::from(role)).insert(account, false);
+        evm::log(RoleRevoked { role, account, sender });
+        Ok(())
+    }
+
+    /// First step of a master transfer, `Address::ZERO` cancels it
+    pub fn propose_master(&mut self, new_master: Address, sender: Address) -> BResult<()> {
+        self.only_role(MASTER_ROLE, sender)?;
+
+        self.pending_master.set(new_master);
+        evm::log(MasterTransferProposed {
+            current_master: sender,
+            pending_master: new_master,
+        });
+        Ok(())
+    }
+
+    /// Second step of a master transfer, called by the proposed master
+    pub fn accept_master(&mut self, sender: Address) -> BResult<()> {
+        let pending_master = self.pending_master.get();
+        if pending_master == Address::ZERO || sender != pending_master {
+            return Err(BitsaveErrors::Unauthorized(Unauthorized { caller: sender }));
+        }
+
+        let previous_master = self.master_address.get();
+        self.master_address.set(pending_master);
+        self.pending_master.set(Address::ZERO);
+        evm::log(MasterTransferred {
+            previous_master,
+            new_master: pending_master,
+        });
+        Ok(())
+    }
+
+    /// Only the listed roles can be granted; master moves through a transfer
+    fn check_role(role: u8) -> BResult<()> {
+        if role == MASTER_ROLE || role > PAUSER_ROLE {
+            return Err(BitsaveErrors::InvalidRole(InvalidRole { role }));
+        }
+        Ok(())
+    }
+}
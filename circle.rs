@@ -0,0 +1,405 @@
+use alloy_primitives::{Address, U256, U8};
+use stylus_sdk::stylus_proc::sol_storage;
+use crate::constants::{
+    CIRCLE_JOIN_WINDOW, MAX_CIRCLE_MEMBERS, MAX_CIRCLE_PERIOD, MIN_CIRCLE_MEMBERS, MIN_CIRCLE_PERIOD,
+};
+use crate::errors::{
+    AlreadyContributed, BResult, BitsaveErrors, InvalidCircle, InvalidPrice, NotCircleMember, RoundNotOver,
+    RoundOver,
+};
+use crate::math;
+use crate::user_data::UserData;
+
+// Rotating savings circle (ajo/esusu): every round each member contributes
+// and the pot goes to the member whose turn it is
+sol_storage! {
+    pub struct SavingsCircle {
+        bool is_valid;
+        address token_id;
+        uint256 contribution;
+        // round length in seconds
+        uint256 period;
+        uint8 penalty_perc;
+        // payout order, member i receives round i's pot
+        address[] members;
+        mapping(address => bool) is_member;
+        mapping(address => bool) has_joined;
+        uint256 joined_count;
+        // members who haven't joined by then can't, and the joined ones leave with a refund
+        uint256 join_deadline;
+        // contributions each member locks on joining, covers as many defaults
+        uint8 collateral_rounds;
+        // each default takes one contribution of it
+        mapping(address => uint256) collateral;
+        // zero until every member has joined
+        uint256 start_time;
+        // contract pause time when the circle started, rounds are pushed back by later pauses
+        uint256 start_paused_time;
+        uint256 current_round;
+        uint256 round_pot;
+        uint256 round_contributions;
+        // round + 1 a member last contributed to
+        mapping(address => uint256) paid_round;
+        mapping(address => uint256) defaults;
+        // payouts and released collateral waiting to be claimed
+        mapping(address => uint256) claimable;
+        bool is_completed;
+    }
+}
+
+/// Outcome of closing a round
+pub struct RoundPayout {
+    pub round: U256,
+    pub recipient: Address,
+    pub payout: U256,
+    /// members who didn't contribute and the penalty taken off their collateral
+    pub defaulters: Vec<(Address, U256)>,
+    /// all penalties, for the penalty pool
+    pub penalty: U256,
+}
+
+impl SavingsCircle {
+    pub fn create(
+        &mut self,
+        token_id: Address,
+        contribution: U256,
+        period: U256,
+        penalty_perc: u8,
+        collateral_rounds: u8,
+        members: &[Address],
+        now: U256,
+    ) -> BResult<()> {
+        // collateral for every round after a member's own covers them fully
+        if members.len() < MIN_CIRCLE_MEMBERS
+            || members.len() > MAX_CIRCLE_MEMBERS
+            || collateral_rounds == 0
+            || usize::from(collateral_rounds) >= members.len()
+            || contribution == U256::ZERO
+            || period < U256::from(MIN_CIRCLE_PERIOD)
+            || period > U256::from(MAX_CIRCLE_PERIOD)
+        {
+            return Err(BitsaveErrors::InvalidCircle(InvalidCircle {}));
+        }
+
+        for member in members {
+            if *member == Address::ZERO || self.is_member.get(*member) {
+                return Err(BitsaveErrors::InvalidCircle(InvalidCircle {}));
+            }
+            self.is_member.insert(*member, true);
+            self.members.push(*member);
+        }
+
+        self.is_valid.set(true);
+        self.token_id.set(token_id);
+        self.contribution.set(contribution);
+        self.period.set(period);
+        self.penalty_perc.set(U8::from(penalty_perc));
+        self.collateral_rounds.set(U8::from(collateral_rounds));
+        self.join_deadline.set(now + U256::from(CIRCLE_JOIN_WINDOW));
+        Ok(())
+    }
+
+    /// Collateral a member locks on joining
+    pub fn collateral_amount(&self) -> BResult<U256> {
+        math::checked_mul(self.contribution.get(), U256::from(self.collateral_rounds.get()))
+    }
+
+    /// Lock a member's collateral before the join deadline, the circle starts once
+    /// everyone is in. Returns whether this join started it
+    pub fn join(&mut self, member: Address, now: U256, paused_time: U256) -> BResult<bool> {
+        if !self.is_valid.get()
+            || self.start_time.get() > U256::ZERO
+            || now >= self.join_deadline.get()
+        {
+            return Err(BitsaveErrors::InvalidCircle(InvalidCircle {}));
+        }
+        if !self.is_member.get(member) || self.has_joined.get(member) {
+            return Err(BitsaveErrors::NotCircleMember(NotCircleMember { member }));
+        }
+
+        self.has_joined.insert(member, true);
+        self.collateral.insert(member, self.collateral_amount()?);
+
+        let joined_count = self.joined_count.get() + U256::from(1);
+        self.joined_count.set(joined_count);
+        if joined_count == U256::from(self.members.len()) {
+            self.start_time.set(now);
+            self.start_paused_time.set(paused_time);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Take a member's collateral back while the circle hasn't started,
+    /// returns the refund
+    pub fn leave(&mut self, member: Address) -> BResult<U256> {
+        if !self.is_valid.get() || self.start_time.get() > U256::ZERO {
+            return Err(BitsaveErrors::InvalidCircle(InvalidCircle {}));
+        }
+        if !self.has_joined.get(member) {
+            return Err(BitsaveErrors::NotCircleMember(NotCircleMember { member }));
+        }
+
+        let refund = self.collateral.get(member);
+        self.has_joined.insert(member, false);
+        self.collateral.insert(member, U256::ZERO);
+        self.joined_count.set(self.joined_count.get() - U256::from(1));
+        Ok(refund)
+    }
+
+    /// Hand a member back their collateral, claimable funds and contribution to the
+    /// current round in an emergency, returns the refund. A circle that hasn't started
+    /// is left as with `leave`, in a running one the member defaults on later rounds
+    pub fn emergency_refund(&mut self, member: Address) -> BResult<U256> {
+        if self.start_time.get() == U256::ZERO {
+            return self.leave(member);
+        }
+        if !self.is_member.get(member) {
+            return Err(BitsaveErrors::NotCircleMember(NotCircleMember { member }));
+        }
+
+        let mut refund =
+            math::checked_add(self.collateral.get(member), self.claimable.get(member))?;
+        self.collateral.insert(member, U256::ZERO);
+        self.claimable.insert(member, U256::ZERO);
+
+        let round = self.current_round.get();
+        if !self.is_completed.get() && self.paid_round.get(member) == round + U256::from(1) {
+            let contribution = self.contribution.get();
+            refund = math::checked_add(refund, contribution)?;
+            self.paid_round.insert(member, round);
+            self.round_pot.set(self.round_pot.get() - contribution);
+            self.round_contributions
+                .set(self.round_contributions.get() - U256::from(1));
+        }
+
+        if refund == U256::ZERO {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}));
+        }
+        Ok(refund)
+    }
+
+    /// Record a member's contribution to the current round, returns the round
+    pub fn contribute(&mut self, member: Address, now: U256, paused_time: U256) -> BResult<U256> {
+        self.require_running()?;
+        if !self.is_member.get(member) {
+            return Err(BitsaveErrors::NotCircleMember(NotCircleMember { member }));
+        }
+
+        let round_end = self.round_end(paused_time)?;
+        if now >= round_end {
+            return Err(BitsaveErrors::RoundOver(RoundOver { round_end }));
+        }
+
+        let round = self.current_round.get();
+        if self.paid_round.get(member) == round + U256::from(1) {
+            return Err(BitsaveErrors::AlreadyContributed(AlreadyContributed {}));
+        }
+
+        self.paid_round.insert(member, round + U256::from(1));
+        self.round_pot
+            .set(math::checked_add(self.round_pot.get(), self.contribution.get())?);
+        self.round_contributions
+            .set(self.round_contributions.get() + U256::from(1));
+        Ok(round)
+    }
+
+    /// Close the current round once everyone contributed or its period is over
+    /// and credit the whole pot to the member whose turn it is. Defaulters lose one
+    /// contribution of collateral to the pot, less the saving penalty; the last round
+    /// releases what's left of every member's collateral
+    pub fn close_round(&mut self, now: U256, paused_time: U256) -> BResult<RoundPayout> {
+        self.require_running()?;
+
+        let members_count = self.members.len();
+        let round_end = self.round_end(paused_time)?;
+        if now < round_end && self.round_contributions.get() < U256::from(members_count) {
+            return Err(BitsaveErrors::RoundNotOver(RoundNotOver { round_end }));
+        }
+
+        let round = self.current_round.get();
+        let contribution = self.contribution.get();
+        let penalty_perc = self.penalty_perc.get();
+        let mut payout = self.round_pot.get();
+        let mut defaulters = Vec::new();
+        let mut total_penalty = U256::ZERO;
+        for i in 0..members_count {
+            let member = self.members.get(i).unwrap_or_default();
+            if self.paid_round.get(member) == round + U256::from(1) {
+                continue;
+            }
+
+            let (collateral_left, covered, penalty) =
+                settle_default(self.collateral.get(member), contribution, penalty_perc);
+            self.collateral.insert(member, collateral_left);
+            self.defaults.insert(member, self.defaults.get(member) + U256::from(1));
+
+            payout = math::checked_add(payout, covered)?;
+            total_penalty = math::checked_add(total_penalty, penalty)?;
+            defaulters.push((member, penalty));
+        }
+
+        let recipient = self
+            .members
+            .get(round.to::<usize>())
+            .unwrap_or_default();
+
+        let recipient_claimable = self.claimable.get(recipient);
+        self.claimable
+            .insert(recipient, math::checked_add(recipient_claimable, payout)?);
+
+        self.current_round.set(round + U256::from(1));
+        self.round_pot.set(U256::ZERO);
+        self.round_contributions.set(U256::ZERO);
+        if round + U256::from(1) == U256::from(members_count) {
+            self.is_completed.set(true);
+            self.release_collateral()?;
+        }
+
+        Ok(RoundPayout {
+            round,
+            recipient,
+            payout,
+            defaulters,
+            penalty: total_penalty,
+        })
+    }
+
+    /// Take a member's claimable payouts and collateral, returns the amount
+    pub fn claim(&mut self, member: Address) -> BResult<U256> {
+        let amount = self.claimable.get(member);
+        if amount == U256::ZERO {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}));
+        }
+        self.claimable.insert(member, U256::ZERO);
+        Ok(amount)
+    }
+
+    /// Deadline of the current round, pushed back by the time the contract
+    /// spent paused since the circle started
+    pub fn round_end(&self, paused_time: U256) -> BResult<U256> {
+        round_deadline(
+            self.start_time.get(),
+            self.current_round.get(),
+            self.period.get(),
+            paused_time.saturating_sub(self.start_paused_time.get()),
+        )
+    }
+
+    pub fn members(&self) -> Vec<Address> {
+        let mut members = Vec::with_capacity(self.members.len());
+        for i in 0..self.members.len() {
+            members.push(self.members.get(i).unwrap_or_default());
+        }
+        members
+    }
+
+    /// Member details: joined, collateral left, contributed to the current round, defaults,
+    /// claimable
+    pub fn member_details(&self, member: Address) -> (bool, U256, bool, U256, U256) {
+        (
+            self.has_joined.get(member),
+            self.collateral.get(member),
+            self.paid_round.get(member) == self.current_round.get() + U256::from(1),
+            self.defaults.get(member),
+            self.claimable.get(member),
+        )
+    }
+
+    /// Move what's left of every member's collateral to their claimable balance
+    fn release_collateral(&mut self) -> BResult<()> {
+        for i in 0..self.members.len() {
+            let member = self.members.get(i).unwrap_or_default();
+            let collateral = self.collateral.get(member);
+            if collateral > U256::ZERO {
+                self.collateral.insert(member, U256::ZERO);
+                let claimable = math::checked_add(self.claimable.get(member), collateral)?;
+                self.claimable.insert(member, claimable);
+            }
+        }
+        Ok(())
+    }
+
+    fn require_running(&self) -> BResult<()> {
+        if !self.is_valid.get() || self.start_time.get() == U256::ZERO || self.is_completed.get() {
+            return Err(BitsaveErrors::InvalidCircle(InvalidCircle {}));
+        }
+        Ok(())
+    }
+}
+
+/// What a missed contribution takes from a member's collateral: one contribution, or all
+/// that's left. Returns the collateral left, the part covering the pot and the penalty
+fn settle_default(collateral: U256, contribution: U256, penalty_perc: U8) -> (U256, U256, U256) {
+    let taken = collateral.min(contribution);
+    let covered = UserData::calculate_balance_from_penalty(taken, penalty_perc);
+    (collateral - taken, covered, taken - covered)
+}
+
+/// End of `round` for a circle started at `start_time`, pushed back by `paused_since_start`
+fn round_deadline(start_time: U256, round: U256, period: U256, paused_since_start: U256) -> BResult<U256> {
+    let rounds = math::checked_add(round, U256::from(1))?;
+    let round_end = math::checked_add(start_time, math::checked_mul(rounds, period)?)?;
+    math::checked_add(round_end, paused_since_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_takes_one_contribution() {
+        // three contributions of collateral, 10% penalty
+        assert_eq!(
+            settle_default(U256::from(300), U256::from(100), U8::from(10)),
+            (U256::from(200), U256::from(90), U256::from(10))
+        );
+        // no penalty, the pot is fully covered
+        assert_eq!(
+            settle_default(U256::from(100), U256::from(100), U8::from(0)),
+            (U256::ZERO, U256::from(100), U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn default_takes_what_is_left() {
+        assert_eq!(
+            settle_default(U256::from(50), U256::from(100), U8::from(10)),
+            (U256::ZERO, U256::from(45), U256::from(5))
+        );
+        assert_eq!(
+            settle_default(U256::ZERO, U256::from(100), U8::from(10)),
+            (U256::ZERO, U256::ZERO, U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn rounds_follow_the_period_and_pauses() {
+        let day = U256::from(86_400);
+        assert_eq!(
+            round_deadline(U256::from(1_000), U256::ZERO, day, U256::ZERO).ok(),
+            Some(U256::from(87_400))
+        );
+        assert_eq!(
+            round_deadline(U256::from(1_000), U256::from(2), day, U256::ZERO).ok(),
+            Some(U256::from(260_200))
+        );
+        // a pause of an hour since the start pushes the round back an hour
+        assert_eq!(
+            round_deadline(U256::from(1_000), U256::ZERO, day, U256::from(3_600)).ok(),
+            Some(U256::from(91_000))
+        );
+    }
+
+    #[test]
+    fn round_deadline_overflow_is_an_error() {
+        assert!(matches!(
+            round_deadline(U256::MAX, U256::ZERO, U256::from(1), U256::ZERO),
+            Err(BitsaveErrors::MathOverflow(_))
+        ));
+        assert!(matches!(
+            round_deadline(U256::ZERO, U256::MAX, U256::from(1), U256::ZERO),
+            Err(BitsaveErrors::MathOverflow(_))
+        ));
+    }
+}
@@ -1,12 +1,81 @@
 
+// Default fees set on init, changeable by the config admin
 pub const MIN_BS_JOIN_FEE: usize = 2;
 pub const BS_SAVING_FEE: usize = 10;
 
+// Fee bounds; flat fees in wei, percentage fee in basis points
+pub const MAX_FLAT_FEE: u64 = 10_000_000_000_000_000;
+pub const MAX_SAVING_FEE_BPS: usize = 1_000;
+pub const BPS_DIVISOR: usize = 10_000;
+pub const MAX_FEE_RECIPIENTS: usize = 10;
+pub const MAX_FEE_CREDITS_PER_REDEEM: usize = 100;
+
+// Points redeem for at most the reward tokens of the interest they came from, 1e18 scaled
+pub const MAX_POINTS_REWARD_RATE: u64 = 1_000_000_000_000_000_000;
+
+// Default saving limits set on init; penalty in percent, lock durations in seconds
+pub const DEFAULT_MIN_PENALTY_PERC: u8 = 0;
+pub const DEFAULT_MAX_PENALTY_PERC: u8 = 50;
+pub const DEFAULT_MIN_LOCK_DURATION: usize = 3600 * 24;
+pub const DEFAULT_MAX_LOCK_DURATION: usize = 3600 * 24 * 365 * 10;
+pub const DEFAULT_MAX_ACTIVE_SAVINGS: u8 = 20;
+
+// User names; lower case letters, digits and underscores
+pub const MIN_USER_NAME_LEN: usize = 3;
+pub const MAX_USER_NAME_LEN: usize = 32;
+
+// Recurring deposits; keeper bounty in basis points of each deposit, interval in seconds
+pub const DEFAULT_KEEPER_BOUNTY_BPS: usize = 10;
+pub const MAX_KEEPER_BOUNTY_BPS: usize = 100;
+pub const MIN_RECURRING_INTERVAL: usize = 3600;
+
+// Savings circles; round period in seconds
+pub const MIN_CIRCLE_MEMBERS: usize = 2;
+pub const MAX_CIRCLE_MEMBERS: usize = 20;
+pub const MIN_CIRCLE_PERIOD: usize = 3600 * 24;
+pub const MAX_CIRCLE_PERIOD: usize = 3600 * 24 * 365;
+// time members have to join a new circle
+pub const CIRCLE_JOIN_WINDOW: usize = 3600 * 24 * 7;
+
+// Inactivity before a beneficiary can claim, in seconds
+pub const MIN_INACTIVITY_PERIOD: usize = 3600 * 24 * 30;
+pub const MAX_INACTIVITY_PERIOD: usize = 3600 * 24 * 365 * 10;
+
+// Withdrawal windows around maturity
+pub const MAX_GRACE_WINDOW: usize = 3600 * 24 * 30;
+pub const MIN_CLAIM_WINDOW: usize = 3600 * 24;
+
+// Saving lifecycle phases
+pub const PHASE_NONE: u8 = 0;
+pub const PHASE_LOCKED: u8 = 1;
+pub const PHASE_GRACE: u8 = 2;
+pub const PHASE_MATURED: u8 = 3;
+pub const PHASE_EXPIRED: u8 = 4;
+// goal reached before maturity, unlocked without penalty or interest
+pub const PHASE_GOAL_REACHED: u8 = 5;
+
 pub const TOTAL_SUPPLY: usize = 15_000_000;
 pub const MAX_SUPPLY: usize = 100_000_000;
 pub const YEARS_IN_SECS: usize = 3600 * 24 * 365;
 
+// Access roles, master holds all of them
+pub const MASTER_ROLE: u8 = 0;
+pub const CONFIG_ADMIN_ROLE: u8 = 1;
+pub const VAULT_UPDATER_ROLE: u8 = 2;
+pub const FEE_COLLECTOR_ROLE: u8 = 3;
+pub const PAUSER_ROLE: u8 = 4;
+
+// Interest models
+pub const BITSAVE_MODEL: u8 = 0;
+pub const FIXED_APR_MODEL: u8 = 1;
+pub const TIERED_MODEL: u8 = 2;
+pub const MAX_APR_BPS: usize = 10_000;
+pub const MAX_INTEREST_TIERS: usize = 10;
+
 pub const HUNDRED: usize = 100;
 
 pub const DIVISOR: usize = 1_000_000_000;
 
+// 1e18 fixed point scale
+pub const WAD: u64 = 1_000_000_000_000_000_000;
+
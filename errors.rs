@@ -9,6 +9,37 @@ sol! {
     error InvalidCall();
     error NotSupported();
     error InvalidSaving();
+    error InvalidToken();
+    error InsufficientPoints();
+    error ContractPaused();
+    error InvalidFee();
+    error Reentrancy();
+    error Unauthorized(address caller);
+    error InvalidAddress();
+    error InvalidRole(uint8 role);
+    error InvalidVault();
+    error MathOverflow();
+    error DivisionByZero();
+    error InvalidInterestModel();
+    error InvalidPenalty(uint8 penalty_perc);
+    error MaturityInPast(uint256 maturity_time);
+    error LockTooShort(uint256 min_lock_duration);
+    error LockTooLong(uint256 max_lock_duration);
+    error InvalidSavingLimits();
+    error InvalidWithdrawalWindows();
+    error TooManySavings(uint8 max_active_savings);
+    error InvalidUserName();
+    error UserNameTaken();
+    error InvalidSchedule();
+    error ScheduleNotDue(uint256 next_execution);
+    error InvalidCircle();
+    error NotCircleMember(address member);
+    error AlreadyContributed();
+    error RoundOver(uint256 round_end);
+    error RoundNotOver(uint256 round_end);
+    error InvalidGoal();
+    error InvalidBeneficiary();
+    error InheritanceNotClaimable(uint256 claimable_at);
 }
 
 pub enum BitsaveErrors {
@@ -19,6 +50,37 @@ pub enum BitsaveErrors {
     InvalidPrice(InvalidPrice),
     InvalidSaving(InvalidSaving),
     NotSupported(NotSupported),
+    InvalidToken(InvalidToken),
+    InsufficientPoints(InsufficientPoints),
+    ContractPaused(ContractPaused),
+    InvalidFee(InvalidFee),
+    Reentrancy(Reentrancy),
+    Unauthorized(Unauthorized),
+    InvalidAddress(InvalidAddress),
+    InvalidRole(InvalidRole),
+    InvalidVault(InvalidVault),
+    MathOverflow(MathOverflow),
+    DivisionByZero(DivisionByZero),
+    InvalidInterestModel(InvalidInterestModel),
+    InvalidPenalty(InvalidPenalty),
+    MaturityInPast(MaturityInPast),
+    LockTooShort(LockTooShort),
+    LockTooLong(LockTooLong),
+    InvalidSavingLimits(InvalidSavingLimits),
+    InvalidWithdrawalWindows(InvalidWithdrawalWindows),
+    TooManySavings(TooManySavings),
+    InvalidUserName(InvalidUserName),
+    UserNameTaken(UserNameTaken),
+    InvalidSchedule(InvalidSchedule),
+    ScheduleNotDue(ScheduleNotDue),
+    InvalidCircle(InvalidCircle),
+    NotCircleMember(NotCircleMember),
+    AlreadyContributed(AlreadyContributed),
+    RoundOver(RoundOver),
+    RoundNotOver(RoundNotOver),
+    InvalidGoal(InvalidGoal),
+    InvalidBeneficiary(InvalidBeneficiary),
+    InheritanceNotClaimable(InheritanceNotClaimable),
 }
 
 impl From<BitsaveErrors> for Vec<u8> {
@@ -31,6 +93,37 @@ impl From<BitsaveErrors> for Vec<u8> {
             BitsaveErrors::NotSupported(err) => err.encode(),
             BitsaveErrors::InvalidCall(err) => err.encode(),
             BitsaveErrors::InvalidSaving(err) => err.encode(),
+            BitsaveErrors::InvalidToken(err) => err.encode(),
+            BitsaveErrors::InsufficientPoints(err) => err.encode(),
+            BitsaveErrors::ContractPaused(err) => err.encode(),
+            BitsaveErrors::InvalidFee(err) => err.encode(),
+            BitsaveErrors::Reentrancy(err) => err.encode(),
+            BitsaveErrors::Unauthorized(err) => err.encode(),
+            BitsaveErrors::InvalidAddress(err) => err.encode(),
+            BitsaveErrors::InvalidRole(err) => err.encode(),
+            BitsaveErrors::InvalidVault(err) => err.encode(),
+            BitsaveErrors::MathOverflow(err) => err.encode(),
+            BitsaveErrors::DivisionByZero(err) => err.encode(),
+            BitsaveErrors::InvalidInterestModel(err) => err.encode(),
+            BitsaveErrors::InvalidPenalty(err) => err.encode(),
+            BitsaveErrors::MaturityInPast(err) => err.encode(),
+            BitsaveErrors::LockTooShort(err) => err.encode(),
+            BitsaveErrors::LockTooLong(err) => err.encode(),
+            BitsaveErrors::InvalidSavingLimits(err) => err.encode(),
+            BitsaveErrors::InvalidWithdrawalWindows(err) => err.encode(),
+            BitsaveErrors::TooManySavings(err) => err.encode(),
+            BitsaveErrors::InvalidUserName(err) => err.encode(),
+            BitsaveErrors::UserNameTaken(err) => err.encode(),
+            BitsaveErrors::InvalidSchedule(err) => err.encode(),
+            BitsaveErrors::ScheduleNotDue(err) => err.encode(),
+            BitsaveErrors::InvalidCircle(err) => err.encode(),
+            BitsaveErrors::NotCircleMember(err) => err.encode(),
+            BitsaveErrors::AlreadyContributed(err) => err.encode(),
+            BitsaveErrors::RoundOver(err) => err.encode(),
+            BitsaveErrors::RoundNotOver(err) => err.encode(),
+            BitsaveErrors::InvalidGoal(err) => err.encode(),
+            BitsaveErrors::InvalidBeneficiary(err) => err.encode(),
+            BitsaveErrors::InheritanceNotClaimable(err) => err.encode(),
         }
     }
 }
@@ -0,0 +1,96 @@
+use alloy_sol_types::sol;
+
+sol! {
+    event UserJoined(address indexed user, uint256 user_id, string user_name);
+    event UserRenamed(address indexed user, string old_name, string new_name);
+    event BeneficiarySet(address indexed user, address indexed beneficiary, uint256 inactivity_period);
+    event BeneficiaryCancelled(address indexed user);
+    event InheritanceClaimed(address indexed user, address indexed beneficiary, uint256 savings_count);
+    event SavingCreated(
+        address indexed user,
+        string name_of_saving,
+        address indexed token_id,
+        uint256 amount,
+        uint256 maturity_time,
+        uint8 penalty_perc,
+        bool is_safe_mode
+    );
+    event SavingIncremented(address indexed user, string name_of_saving, address indexed token_id, uint256 amount);
+    event SavingGoalSet(address indexed user, string name_of_saving, uint256 goal_amount, bool unlock_on_goal);
+    event SavingGoalReached(address indexed user, string name_of_saving, uint256 goal_amount);
+    event SavingWithdrawn(
+        address indexed user,
+        string name_of_saving,
+        address indexed token_id,
+        uint256 amount,
+        uint256 penalty,
+        uint256 interest,
+        uint256 pool_share
+    );
+    event ConfigChanged(address router_address, address stablecoin_address, address collector_address);
+    event VaultUpdated(uint256 vault_state, uint256 total_value_locked);
+    event VaultOverrideChanged(bool is_overridden);
+    event InterestModelChanged(address indexed token_id, bool is_default, uint8 kind, uint256 apr_bps);
+    event SavingLimitsChanged(
+        uint8 min_penalty_perc,
+        uint8 max_penalty_perc,
+        uint256 min_lock_duration,
+        uint256 max_lock_duration
+    );
+    event MaxActiveSavingsChanged(uint8 max_active_savings);
+    event WithdrawalWindowsChanged(uint256 grace_window, uint256 claim_window);
+    event FeesChanged(uint256 join_fee, uint256 saving_fee, uint256 saving_fee_bps);
+    event FeeSplitChanged(address[] recipients, uint256[] shares_bps);
+    event FeesClaimed(address indexed token_id, address indexed claimer, uint256 amount);
+    event KeeperBountyChanged(uint256 keeper_bounty_bps);
+
+    event RecurringDepositScheduled(
+        address indexed user,
+        string name_of_saving,
+        address indexed token_id,
+        uint256 amount,
+        uint256 interval,
+        uint256 end_time
+    );
+    event RecurringDepositCancelled(address indexed user, string name_of_saving);
+
+    event CircleCreated(
+        uint256 indexed circle_id,
+        address indexed creator,
+        address token_id,
+        uint256 contribution,
+        uint256 period,
+        uint256 members_count
+    );
+    event CircleJoined(uint256 indexed circle_id, address indexed member);
+    event CircleLeft(uint256 indexed circle_id, address indexed member, uint256 refund);
+    event CircleStarted(uint256 indexed circle_id, uint256 start_time);
+    event CircleContribution(uint256 indexed circle_id, address indexed member, uint256 round, uint256 amount);
+    event CircleDefault(uint256 indexed circle_id, address indexed member, uint256 round, uint256 penalty);
+    event CirclePayout(uint256 indexed circle_id, address indexed recipient, uint256 round, uint256 amount);
+    event CircleCompleted(uint256 indexed circle_id);
+    event CircleFundsClaimed(uint256 indexed circle_id, address indexed member, uint256 amount);
+    event RecurringDepositExecuted(
+        address indexed user,
+        string name_of_saving,
+        address indexed keeper,
+        uint256 amount,
+        uint256 bounty,
+        uint256 missed
+    );
+
+    event RoleGranted(uint8 indexed role, address indexed account, address indexed sender);
+    event RoleRevoked(uint8 indexed role, address indexed account, address indexed sender);
+    event MasterTransferProposed(address indexed current_master, address indexed pending_master);
+    event MasterTransferred(address indexed previous_master, address indexed new_master);
+
+    event Paused(address indexed guardian);
+    event Unpaused(address indexed guardian);
+    event EmergencyModeChanged(address indexed guardian, bool enabled);
+    event EmergencyWithdrawn(address indexed user, string name_of_saving, address indexed token_id, uint256 amount);
+
+    event PointsUpdated(address indexed user, uint256 previous_points, uint256 new_points);
+    event PointsRatesChanged(uint256 reward_rate, uint256 points_per_fee_credit);
+    event RewardTokenChanged(address reward_token);
+    event PointsRedeemed(address indexed user, uint256 points, uint256 reward_amount, uint256 fee_credits);
+}
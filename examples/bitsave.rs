@@ -45,16 +45,17 @@ async fn main() -> eyre::Result<()> {
         Bitsave,
         r#"[
             function getBitsaveUserCount() external view returns (uint256)
-            function getUserDetails(uint8[] memory username) external view returns (string memory, uint256, address)
+            function getUserDetails(address user_address) external view returns (string memory, uint256, address)
+            function getUserDetailsByName(string calldata user_name) external view returns (string memory, uint256, address)
             function getBitsaveBalance() external view returns (uint256)
-            function getAccumulatedPool() external view returns (uint256)
-            function getTokensBalance() external view returns (uint256)
+            function getAccumulatedPool(address token_id) external view returns (uint256)
+            function getTokensBalance(address token_id) external view returns (uint256)
 
-            function joinBitsave(uint8[] calldata user_name) external payable returns (address)
+            function joinBitsave(string calldata user_name) external payable returns (address)
             function fund() external payable returns (uint256)
-            function createSaving(string calldata name_of_saving, uint256 maturity_time, uint8 penalty_perc, bool use_safe_mode) external
-            function incrementSaving(string calldata name_of_saving) external
-            function withdrawSavings(string calldata name_of_saving) external returns (uint256)
+            function createSaving(string calldata name_of_saving, uint256 maturity_time, uint8 penalty_perc, bool use_safe_mode, uint256 min_amount_out, uint256 goal_amount, bool unlock_on_goal) external payable
+            function incrementSaving(string calldata name_of_saving, uint256 min_amount_out) external payable
+            function withdrawSavings(string calldata name_of_saving) external returns (uint256, uint256, uint256)
         ]"#
     );
 
@@ -79,27 +80,32 @@ async fn main() -> eyre::Result<()> {
     println!("Balance: {:?}", bitsave_balance);
 
     // let join_res = bitsave
-    //     .join_bitsave("me".as_bytes().to_vec())
+    //     .join_bitsave("bitsaver".to_string())
     //     .send()
     //     .await?
     //     .await?;
     // println!("Join bitsave return value = {:?}", join_res);
 
-    println!("{:?}", "xpan".as_bytes().to_vec());
     let user_data = bitsave
-        .get_user_details("xpan".as_bytes().to_vec())
+        .get_user_details(wallet.address())
         .call()
         .await;
     println!("{:?}", user_data);
     if let Ok((username, user_id, user_address)) = user_data {
         println!(
             "User details\nName: {:?}\nId: {user_id}\nAddress: {user_address}",
-            String::from_utf8(username.into()).unwrap()
+            username
         );
     } else {
         println!("Err decoding {:?}", user_data);
     }
 
+    let user_by_name = bitsave
+        .get_user_details_by_name("bitsaver".to_string())
+        .call()
+        .await;
+    println!("User by name = {:?}", user_by_name);
+
     // if let Err(ContractErr   or::Revert(Bytes(join_val))) = join_res {
     //     println!("{:?}", String::from_utf8(join_val.encode()));
     // };
@@ -108,7 +114,7 @@ async fn main() -> eyre::Result<()> {
     println!("Bitsave user count = {:?}", count_res);
 
     // let create_res = bitsave
-    //     .create_saving("schoolFee".to_string(), 1714242866.into(), 2, false)
+    //     .create_saving("schoolFee".to_string(), 1714242866.into(), 2, false, 0.into(), 0.into(), false)
     //     .call()
     //     .await;
     // println!("Create saving bitsave return value = {:?}", create_res);
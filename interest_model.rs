@@ -0,0 +1,176 @@
+use alloy_primitives::{U256, U8};
+use stylus_sdk::{block, storage::Erase, stylus_proc::sol_storage};
+use crate::constants::{
+    BITSAVE_MODEL, BPS_DIVISOR, DIVISOR, FIXED_APR_MODEL, HUNDRED, MAX_APR_BPS, MAX_INTEREST_TIERS, MAX_SUPPLY,
+    TIERED_MODEL, TOTAL_SUPPLY, WAD, YEARS_IN_SECS,
+};
+use crate::errors::{BResult, BitsaveErrors, InvalidInterestModel, InvalidVault};
+use crate::math;
+
+sol_storage! {
+    pub struct InterestModel {
+        bool is_set;
+        // bitsave formula, fixed apr or tiered by lock duration
+        uint8 kind;
+        uint256 apr_bps;
+        // ascending minimum lock durations in seconds and their apr
+        uint256[] tier_durations;
+        uint256[] tier_apr_bps;
+    }
+}
+
+impl InterestModel {
+    pub fn set(
+        &mut self,
+        kind: u8,
+        apr_bps: U256,
+        tier_durations: &[U256],
+        tier_apr_bps: &[U256],
+    ) -> BResult<()> {
+        let max_apr = U256::from(MAX_APR_BPS);
+        if kind > TIERED_MODEL || apr_bps > max_apr {
+            return Err(BitsaveErrors::InvalidInterestModel(InvalidInterestModel {}));
+        }
+
+        // tiers only make sense for the tiered model
+        if kind == TIERED_MODEL {
+            if tier_durations.is_empty()
+                || tier_durations.len() != tier_apr_bps.len()
+                || tier_durations.len() > MAX_INTEREST_TIERS
+                || tier_durations.windows(2).any(|pair| pair[0] >= pair[1])
+                || tier_apr_bps.iter().any(|apr| *apr > max_apr)
+            {
+                return Err(BitsaveErrors::InvalidInterestModel(InvalidInterestModel {}));
+            }
+        } else if !tier_durations.is_empty() || !tier_apr_bps.is_empty() {
+            return Err(BitsaveErrors::InvalidInterestModel(InvalidInterestModel {}));
+        }
+
+        self.clear();
+        self.is_set.set(true);
+        self.kind.set(U8::from(kind));
+        self.apr_bps.set(apr_bps);
+        for (duration, apr) in tier_durations.iter().zip(tier_apr_bps.iter()) {
+            self.tier_durations.push(*duration);
+            self.tier_apr_bps.push(*apr);
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.is_set.set(false);
+        self.kind.set(U8::from(0));
+        self.apr_bps.set(U256::ZERO);
+        self.tier_durations.erase();
+        self.tier_apr_bps.erase();
+    }
+
+    pub fn kind(&self) -> u8 {
+        self.kind.get().to::<u8>()
+    }
+
+    /// Model details: kind, apr and tiers
+    pub fn details(&self) -> (u8, U256, Vec<U256>, Vec<U256>) {
+        let mut tier_durations = Vec::with_capacity(self.tier_durations.len());
+        let mut tier_apr_bps = Vec::with_capacity(self.tier_durations.len());
+        for i in 0..self.tier_durations.len() {
+            tier_durations.push(self.tier_durations.get(i).unwrap_or_default());
+            tier_apr_bps.push(self.tier_apr_bps.get(i).unwrap_or_default());
+        }
+        (self.kind(), self.apr_bps.get(), tier_durations, tier_apr_bps)
+    }
+
+    /// Interest earned by `amount` from now until `end_time`
+    /// on a saving locked from `start_time`
+    pub fn interest(
+        &self,
+        amount: U256,
+        start_time: U256,
+        end_time: U256,
+        vault_state: U256,
+        total_value_locked: U256,
+    ) -> BResult<U256> {
+        match self.kind() {
+            BITSAVE_MODEL => Self::bitsave_interest(amount, end_time, vault_state, total_value_locked),
+            FIXED_APR_MODEL => Self::apr_interest(amount, self.apr_bps.get(), end_time),
+            TIERED_MODEL => {
+                let lock_duration = end_time.saturating_sub(start_time);
+                Self::apr_interest(amount, self.tier_apr(lock_duration), end_time)
+            }
+            _ => Err(BitsaveErrors::InvalidInterestModel(InvalidInterestModel {})),
+        }
+    }
+
+    /// Apr of the longest tier the lock duration reaches, none below the first tier
+    fn tier_apr(&self, lock_duration: U256) -> U256 {
+        let mut apr_bps = U256::ZERO;
+        for i in 0..self.tier_durations.len() {
+            if self.tier_durations.get(i).unwrap_or_default() > lock_duration {
+                break;
+            }
+            apr_bps = self.tier_apr_bps.get(i).unwrap_or_default();
+        }
+        apr_bps
+    }
+
+    /// amount * apr * fractional years left to maturity, amount in 18 decimals
+    fn apr_interest(amount: U256, apr_bps: U256, end_time: U256) -> BResult<U256> {
+        let now = U256::from(block::timestamp());
+        if amount == U256::ZERO || apr_bps == U256::ZERO || end_time <= now {
+            return Ok(U256::ZERO);
+        }
+
+        let years_taken = math::wad_div(end_time - now, U256::from(YEARS_IN_SECS))?;
+        let yearly_interest = math::mul_div(amount, apr_bps, U256::from(BPS_DIVISOR))?;
+        math::wad_mul(yearly_interest, years_taken)
+    }
+
+    /// bitsave interest calculator:
+    /// Uses bitsave formulae; to be integrated through the bitsave's token.
+    /// Runs in 1e18 fixed point so savings under a year still earn;
+    /// nothing earns until there is a vault state and value locked, or once
+    /// the whole supply is out
+    fn bitsave_interest(
+        amount: U256,
+        end_time: U256,
+        // internal data
+        vault_state: U256,
+        total_value_locked: U256
+    ) -> BResult<U256> {
+
+        let total_supply: U256 = U256::from(TOTAL_SUPPLY);
+        let max_supply: U256 = U256::from(MAX_SUPPLY);
+        let years_in_second: U256 = U256::from(YEARS_IN_SECS);
+        let hundred: U256 = U256::from(HUNDRED);
+        let divisor: U256 = U256::from(DIVISOR);
+
+        if vault_state > total_supply {
+            return Err(BitsaveErrors::InvalidVault(InvalidVault {}));
+        }
+
+        let now = U256::from(block::timestamp());
+        if amount == U256::ZERO
+            || vault_state == U256::ZERO
+            || vault_state == total_supply
+            || total_value_locked == U256::ZERO
+            || end_time <= now
+        {
+            return Ok(U256::ZERO);
+        }
+
+        // crp = ((total_supply - vault_state) / vault_state) * 100
+        let crp = math::checked_mul(math::wad_div(total_supply - vault_state, vault_state)?, hundred)?;
+        // fractional years left to maturity
+        let years_taken = math::wad_div(end_time - now, years_in_second)?;
+
+        // amount * bs_rate * years_taken / (100 * divisor), bs_rate = max_supply / (crp * tvl);
+        // crp and years_taken are both 1e18 scaled so their scales cancel out, tvl is in
+        // 18 decimal base units so one more 1e18 brings it back to whole tokens like vault_state
+        let numerator = math::checked_mul(math::checked_mul(amount, max_supply)?, years_taken)?;
+        let denominator = math::checked_mul(
+            math::checked_mul(crp, total_value_locked)?,
+            hundred * divisor,
+        )?;
+        math::mul_div(numerator, U256::from(WAD), denominator)
+    }
+}
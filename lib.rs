@@ -15,20 +15,46 @@
 
 extern crate alloc;
 
-use crate::constants::{BS_SAVING_FEE, MIN_BS_JOIN_FEE};
+use crate::constants::{
+    BPS_DIVISOR, BS_SAVING_FEE, CONFIG_ADMIN_ROLE, DEFAULT_KEEPER_BOUNTY_BPS, DEFAULT_MAX_ACTIVE_SAVINGS,
+    DEFAULT_MAX_LOCK_DURATION, DEFAULT_MAX_PENALTY_PERC,
+    DEFAULT_MIN_LOCK_DURATION, DEFAULT_MIN_PENALTY_PERC, HUNDRED, MAX_GRACE_WINDOW, MIN_CLAIM_WINDOW, FEE_COLLECTOR_ROLE, MAX_FEE_CREDITS_PER_REDEEM, MAX_FEE_RECIPIENTS, MAX_FLAT_FEE, MAX_INACTIVITY_PERIOD, MAX_KEEPER_BOUNTY_BPS, MAX_POINTS_REWARD_RATE,
+    MAX_SAVING_FEE_BPS, MAX_USER_NAME_LEN, MIN_BS_JOIN_FEE, MIN_INACTIVITY_PERIOD, MIN_USER_NAME_LEN, PAUSER_ROLE, TOTAL_SUPPLY, VAULT_UPDATER_ROLE, WAD,
+};
 use crate::errors::BitsaveErrors::InvalidCall;
 use crate::errors::{
-    BResult, BitsaveErrors, GeneralError, InvalidPrice, NotSupported, InvalidUser,
+    BResult, BitsaveErrors, ContractPaused, InvalidFee, InvalidPrice, InvalidToken, NotSupported,
+    InheritanceNotClaimable, InvalidAddress, InvalidBeneficiary, InvalidCircle, InvalidPenalty, InvalidSaving, InvalidSavingLimits, InvalidSchedule, InvalidUser, InvalidUserName, InvalidVault,
+    InvalidWithdrawalWindows, LockTooLong,
+    LockTooShort, MaturityInPast, Reentrancy, TooManySavings, Unauthorized, UserNameTaken,
+};
+use crate::events::{
+    BeneficiaryCancelled, BeneficiarySet, InheritanceClaimed,
+    CircleCompleted, CircleContribution, CircleCreated, CircleDefault, CircleFundsClaimed, CircleJoined, CircleLeft, CirclePayout,
+    CircleStarted,
+    ConfigChanged, EmergencyModeChanged, EmergencyWithdrawn, FeeSplitChanged, FeesChanged,
+    FeesClaimed, InterestModelChanged, KeeperBountyChanged, MaxActiveSavingsChanged, Paused, SavingLimitsChanged, WithdrawalWindowsChanged, PointsRatesChanged, PointsRedeemed, RewardTokenChanged, RecurringDepositCancelled,
+    RecurringDepositExecuted, RecurringDepositScheduled, SavingCreated, SavingGoalSet,
+    SavingIncremented, SavingWithdrawn, Unpaused, UserJoined, UserRenamed, VaultOverrideChanged, VaultUpdated,
 };
-use alloy_primitives::{Address, U256};
-use stylus_sdk::call::{call, transfer_eth, Call};
+use alloy_primitives::{Address, U256, U8};
+use stylus_sdk::call::{call, Call};
 /// Import items from the SDK. The prelude contains common traits and macros.
-use stylus_sdk::{msg, prelude::*};
+use stylus_sdk::{block, contract, evm, msg, prelude::*, storage::Erase};
 /// Import user library and other fns
 use user_data::UserData;
+use access_control::AccessControl;
+use circle::SavingsCircle;
+use interest_model::InterestModel;
 
+mod access_control;
+mod circle;
 mod constants;
 mod errors;
+mod events;
+mod interest_model;
+mod math;
+mod recurring;
 mod user_data;
 
 pub type RResult<T, E = Vec<u8>> = core::result::Result<T, E>;
@@ -40,101 +66,201 @@ sol_storage! {
     pub struct Bitsave {
         // Maintenance details
         bool initialized;
-        address master_address;
+        AccessControl access_control;
         address collector_address;
+        bool paused;
+        // when the current pause began, and the time spent in earlier pauses
+        uint256 paused_at;
+        uint256 paused_time;
+        bool locked;
+        // paused, and savers can pull their principal penalty free
+        bool emergency_mode;
+
+        // Fees; flat fees in native token, percentage fee in basis points
+        uint256 join_fee;
+        uint256 saving_fee;
+        uint256 saving_fee_bps;
+        // fees waiting to be claimed, per token, and how claims are split
+        mapping(address => uint256) unclaimed_fees;
+        address[] fee_recipients;
+        uint256[] fee_recipient_bps;
+        // paid to keepers out of each recurring deposit they execute
+        uint256 keeper_bounty_bps;
+
+        // Saving limits; penalty in percent, lock durations in seconds
+        uint8 min_penalty_perc;
+        uint8 max_penalty_perc;
+        uint256 min_lock_duration;
+        uint256 max_lock_duration;
+        uint8 max_active_savings;
+        // penalty free window before maturity, window after it to claim interest
+        uint256 grace_window;
+        uint256 claim_window;
 
         // SWAP Details
         address router_address;
         address stablecoin_address;
 
+        // Reward details
+        address reward_token_address;
+        uint256 reward_pool_balance;
+
+        // Interest models; per token models take over the default
+        InterestModel default_interest_model;
+        mapping(address => InterestModel) token_interest_models;
+
+        // Point redemption; reward tokens per point (1e18 scaled), points per fee credit
+        uint256 points_reward_rate;
+        uint256 points_per_fee_credit;
+
         // collection details
         uint256 vault_state;
+        // set by an admin override, stops following the reward token supply until released
+        bool vault_state_overridden;
+        // tracked on every deposit and withdrawal, normalized to 18 decimals
         uint256 total_value_locked;
+        mapping(address => uint256) token_value_locked;
+        // 10^(18 - decimals) per token, zero until first seen
+        mapping(address => uint256) token_scale;
 
         uint256 user_count;
-        uint256 accumulated_pool_balance;
+        // user name registry, name => member
+        mapping(string => address) user_names;
         uint256 general_fund;
+
+        // Penalty pool, per token
+        mapping(address => uint256) accumulated_pool_balance;
+        mapping(address => uint256) pool_share_index;
+        mapping(address => uint256) pool_total_shares;
+        mapping(address => uint256) pool_undistributed;
         mapping(address => UserData) users_mapping;
+
+        // Savings circles, by id from 1
+        uint256 circle_count;
+        mapping(uint256 => SavingsCircle) circles;
     }
 }
 
-// sol_interface! {
-//     interface IUniswapV2Router {
-//         function swapExactETHForTokens(
-//             uint amountOutMin,
-//             address[] calldata path,
-//             address to,
-//             uint deadline
-//         ) external payable returns (uint[] memory amounts);
-//     }
-//
-//     interface IERC20 {
-//         function transfer(address recipient, uint256 amount) external returns (bool);
-//         function balanceOf(address account) external view returns (uint256);
-//     }
-// }
+sol_interface! {
+    interface IUniswapV2Router {
+        function WETH() external pure returns (address);
+
+        function swapExactETHForTokens(
+            uint amountOutMin,
+            address[] calldata path,
+            address to,
+            uint deadline
+        ) external payable returns (uint[] memory amounts);
+
+        function swapExactTokensForTokens(
+            uint amountIn,
+            uint amountOutMin,
+            address[] calldata path,
+            address to,
+            uint deadline
+        ) external returns (uint[] memory amounts);
+    }
+
+    interface IERC20 {
+        function transfer(address recipient, uint256 amount) external returns (bool);
+        function approve(address spender, uint256 amount) external returns (bool);
+        function transferFrom(address sender, address recipient, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+        function totalSupply() external view returns (uint256);
+        function decimals() external view returns (uint8);
+    }
+}
 
 /// Declare that `Bitsave` is a contract with the following external methods.
 #[public]
 impl Bitsave {
-    fn require_master(&self, sender: Address) -> RResult<()> {
-        if sender != self.master_address.get() {
-            return Err(BitsaveErrors::GeneralError(GeneralError {
-                msg: "Not authorized".to_string(),
-            })
-            .into());
+    fn require_role(&self, role: u8, sender: Address) -> RResult<()> {
+        Ok(self.access_control.only_role(role, sender)?)
+    }
+
+    /// Reentrancy lock for value moving methods; a failing call reverts
+    /// the lock with everything else so only the success path unlocks
+    fn lock(&mut self) -> RResult<()> {
+        if self.locked.get() {
+            return Err(BitsaveErrors::Reentrancy(Reentrancy {}).into());
         }
+        self.locked.set(true);
         Ok(())
     }
 
-    /// Initialize data
-    pub fn init(&mut self) {
-        if !self.initialized.get() {
-            self.master_address.set(msg::sender());
-            self.collector_address.set(msg::sender());
-            self.general_fund.set(U256::from(0));
-            self.initialized.set(true);
+    fn unlock(&mut self) {
+        self.locked.set(false);
+    }
+
+    fn require_not_paused(&self) -> RResult<()> {
+        if self.paused.get() {
+            return Err(BitsaveErrors::ContractPaused(ContractPaused {}).into());
         }
+        Ok(())
     }
 
-    pub fn change_data(
-        &mut self,
-        router_address: Address,
-        stablecoin_address: Address,
-        collector_address: Address,
-    ) {
-        self.require_master(msg::sender()).unwrap();
+    fn set_paused(&mut self) {
+        if !self.paused.get() {
+            self.paused_at.set(U256::from(block::timestamp()));
+            self.paused.set(true);
+        }
+    }
 
-        self.router_address.set(router_address);
-        self.stablecoin_address.set(stablecoin_address);
-        self.collector_address.set(collector_address);
+    /// Total time spent paused, circle deadlines move by it
+    fn total_paused_time(&self) -> U256 {
+        if self.paused.get() {
+            self.paused_time.get() + (U256::from(block::timestamp()) - self.paused_at.get())
+        } else {
+            self.paused_time.get()
+        }
+    }
+
+    /// Savings are for members only
+    fn require_member(&self, user_address: Address) -> RResult<()> {
+        if !self.users_mapping.get(user_address).user_exists.get() {
+            return Err(BitsaveErrors::InvalidUser(InvalidUser {}).into());
+        }
+        Ok(())
     }
 
-    pub fn update_vault(&mut self, v_state: U256, total_locked: U256) {
-        self.require_master(msg::sender()).unwrap();
+    /// Check a user name's length and characters and that it is free,
+    /// then point it to `user_address`
+    fn claim_user_name(&mut self, user_name: String, user_address: Address) -> RResult<()> {
+        let valid_chars = user_name
+            .bytes()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'_');
+        if !valid_chars
+            || user_name.len() < MIN_USER_NAME_LEN
+            || user_name.len() > MAX_USER_NAME_LEN
+        {
+            return Err(BitsaveErrors::InvalidUserName(InvalidUserName {}).into());
+        }
 
-        self.vault_state.set(v_state);
-        self.total_value_locked.set(total_locked);
+        if self.user_names.get(user_name.clone()) != Address::ZERO {
+            return Err(BitsaveErrors::UserNameTaken(UserNameTaken {}).into());
+        }
+        self.user_names.insert(user_name, user_address);
+        Ok(())
     }
 
-    /// My gathered points
-    pub fn get_user_points(&self) -> BResult<U256> {
-        Ok(self.users_mapping.get(msg::sender()).total_point.get())
+    /// Keep the sender's inactivity clock from running out, members only
+    fn record_activity(&mut self) {
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        if user_updater.user_exists.get() {
+            user_updater
+                .last_activity
+                .set(U256::from(block::timestamp()));
+        }
     }
 
-    /// Join the space
-    #[payable]
-    pub fn join_bitsave(&mut self, user_name: String) -> RResult<Address> {
+    /// Register the sender as a member, the join fee is handled by the caller
+    fn register_user(&mut self, user_name: String) -> RResult<Address> {
         // check user doesn't exist
         let fetched_user = self.users_mapping.get(msg::sender());
         if fetched_user.user_exists.get() {
             return Err(BitsaveErrors::InvalidUser(InvalidUser {}).into())
         };
-
-        // check for joining fee
-        if msg::value() < U256::from(MIN_BS_JOIN_FEE) {
-            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
-        }
+        self.claim_user_name(user_name.clone(), msg::sender())?;
 
         // incr user count
         let new_user_count = self.user_count.get() + U256::from(1);
@@ -142,94 +268,2231 @@ impl Bitsave {
 
         let mut fetched_user = self.users_mapping.setter(msg::sender());
         // update user data
-        fetched_user.create_user(msg::sender(), new_user_count, user_name);
+        fetched_user.create_user(msg::sender(), new_user_count, user_name.clone());
+        fetched_user
+            .last_activity
+            .set(U256::from(block::timestamp()));
+
+        evm::log(UserJoined {
+            user: msg::sender(),
+            user_id: new_user_count,
+            user_name,
+        });
 
-        // return user exists txn
         Ok(self.users_mapping.get(msg::sender()).user_address.get())
     }
 
-    /// Create a new saving
-    #[payable]
-    pub fn create_saving(
+    /// Pull `amount` of an erc20 token from `from` into the contract, returns what
+    /// actually arrived so fee-on-transfer tokens are accounted for
+    fn receive_token(&mut self, token_id: Address, from: Address, amount: U256) -> RResult<U256> {
+        let token = IERC20::new(token_id);
+        let balance_before = token.balance_of(Call::new_in(self), contract::address())?;
+        let received = token.transfer_from(Call::new_in(self), from, contract::address(), amount)?;
+        if !received {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+        let balance_after = token.balance_of(Call::new_in(self), contract::address())?;
+        if balance_after < balance_before {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+        Ok(balance_after - balance_before)
+    }
+
+    /// Take an exact payment from the sender, native value or erc20 `transferFrom`
+    fn receive_payment(&mut self, token_id: Address, amount: U256) -> RResult<()> {
+        if token_id == Address::ZERO {
+            if msg::value() != amount {
+                return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+            }
+            return Ok(());
+        }
+        // native value sent along a token payment would be left untracked
+        if msg::value() != U256::ZERO {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+        if self.receive_token(token_id, msg::sender(), amount)? != amount {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+        Ok(())
+    }
+
+    /// Send funds out of the contract; native token is `Address::ZERO`
+    fn send_funds(&mut self, token_id: Address, to: Address, amount: U256) -> RResult<()> {
+        if token_id == Address::ZERO {
+            call(Call::new_in(self).value(amount), to, &[])?;
+            return Ok(());
+        }
+
+        let token = IERC20::new(token_id);
+        let sent = token.transfer(Call::new_in(self), to, amount)?;
+        if !sent {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+        Ok(())
+    }
+
+    /// Swap `amount` of `token_id` into the stablecoin through the router,
+    /// returns the amount of stablecoin received; reverts below `min_amount_out`
+    fn swap_to_stablecoin(
+        &mut self,
+        token_id: Address,
+        amount: U256,
+        min_amount_out: U256,
+    ) -> RResult<U256> {
+        let stablecoin = self.stablecoin_address.get();
+        let router_address = self.router_address.get();
+        if stablecoin == Address::ZERO || router_address == Address::ZERO {
+            // safe mode not configured
+            return Err(BitsaveErrors::NotSupported(NotSupported {}).into());
+        }
+
+        // already in stablecoin, nothing to swap
+        if token_id == stablecoin {
+            return Ok(amount);
+        }
+
+        let router = IUniswapV2Router::new(router_address);
+        let deadline = U256::from(block::timestamp());
+
+        let amounts = if token_id == Address::ZERO {
+            let weth = router.weth(Call::new_in(self))?;
+            router.swap_exact_eth_for_tokens(
+                Call::new_in(self).value(amount),
+                min_amount_out,
+                vec![weth, stablecoin],
+                contract::address(),
+                deadline,
+            )?
+        } else {
+            let token = IERC20::new(token_id);
+            let approved = token.approve(Call::new_in(self), router_address, amount)?;
+            if !approved {
+                return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+            }
+            router.swap_exact_tokens_for_tokens(
+                Call::new_in(self),
+                amount,
+                min_amount_out,
+                vec![token_id, stablecoin],
+                contract::address(),
+                deadline,
+            )?
+        };
+
+        match amounts.last() {
+            Some(received) if *received > U256::ZERO && *received >= min_amount_out => Ok(*received),
+            _ => Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into()),
+        }
+    }
+
+    /// Take matured interest out of the reward pool, returns the reward token and
+    /// the amount to send; whatever the pool can't cover stays pending on the user
+    fn take_interest_from_pool(&mut self, user: Address, name_of_saving: String, interest: U256) -> (Address, U256) {
+        let reward_token = self.reward_token_address.get();
+        let pool_balance = self.reward_pool_balance.get();
+
+        let interest_paid = if reward_token == Address::ZERO {
+            U256::ZERO
+        } else {
+            interest.min(pool_balance)
+        };
+
+        self.reward_pool_balance.set(pool_balance - interest_paid);
+        let mut user_updater = self.users_mapping.setter(user);
+        user_updater.settle_interest(name_of_saving, interest_paid);
+
+        (reward_token, interest_paid)
+    }
+
+    /// Collect the saving fee, returns the amount left to save.
+    /// The flat fee is always paid from `native_value`, the part of the value sent along
+    /// left for saving; the percentage fee comes out of `amount` in the saving's token.
+    /// A fee credit from points waives both. Token savings send exactly the flat fee,
+    /// or nothing with a fee credit, so no native value is left untracked
+    fn collect_saving_fee(
         &mut self,
+        token_id: Address,
+        native_value: U256,
+        amount: U256,
+    ) -> RResult<U256> {
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        let fee_credits = user_updater.fee_credits.get();
+        if fee_credits > U256::ZERO {
+            if token_id != Address::ZERO && native_value != U256::ZERO {
+                return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+            }
+            user_updater.fee_credits.set(fee_credits - U256::from(1));
+            return Ok(amount);
+        }
+
+        let flat_fee = self.saving_fee.get();
+        if native_value < flat_fee || (token_id != Address::ZERO && native_value != flat_fee) {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+
+        let (perc_fee, amount_of_saving) = if token_id == Address::ZERO {
+            // native amount already includes the flat fee
+            let rest = amount - flat_fee;
+            let bps_divisor = U256::from(BPS_DIVISOR);
+            let saved = rest * bps_divisor / (bps_divisor + self.saving_fee_bps.get());
+            (rest - saved, saved)
+        } else {
+            let perc_fee = amount * self.saving_fee_bps.get() / U256::from(BPS_DIVISOR);
+            (perc_fee, amount - perc_fee)
+        };
+
+        // Keep fees for the collector to claim
+        if token_id == Address::ZERO {
+            self.accrue_fee(Address::ZERO, flat_fee + perc_fee);
+        } else {
+            self.accrue_fee(Address::ZERO, flat_fee);
+            self.accrue_fee(token_id, perc_fee);
+        }
+
+        Ok(amount_of_saving)
+    }
+
+    fn accrue_fee(&mut self, token_id: Address, amount: U256) {
+        let unclaimed_fees = self.unclaimed_fees.get(token_id);
+        self.unclaimed_fees.insert(token_id, unclaimed_fees + amount);
+    }
+
+    /// Check a recurring deposit fits the saving: erc20 only, the saving's token
+    /// unless safe mode swaps it, and no deposits after maturity
+    fn check_recurring_terms(
+        &self,
+        user: Address,
         name_of_saving: String,
-        maturity_time: U256,
-        penalty_perc: u8,
-        use_safe_mode: bool,
+        token_id: Address,
+        end_time: U256,
     ) -> RResult<()> {
-        // retrieve some data
-        // fetch user's data
+        let user_data = self.users_mapping.get(user);
+        let saving_data = user_data.savings_map.get(name_of_saving);
+        if !saving_data.is_valid.get() {
+            return Err(BitsaveErrors::InvalidSaving(InvalidSaving {}).into());
+        }
 
-        let amount_received = msg::value();
-        let saving_fee = U256::from(BS_SAVING_FEE);
-        if amount_received < saving_fee {
-            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        if token_id == Address::ZERO
+            || (!saving_data.is_safe_mode.get() && token_id != saving_data.token_id.get())
+        {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
         }
+        if end_time > saving_data.maturity_time.get() {
+            return Err(BitsaveErrors::InvalidSchedule(InvalidSchedule {}).into());
+        }
+        Ok(())
+    }
 
-        // Send fee to collector address
-        transfer_eth(self.collector_address.get(), saving_fee)?;
+    /// Check a new saving's penalty, maturity and the sender's active savings
+    /// against the saving limits
+    fn validate_saving_terms(&self, maturity_time: U256, penalty_perc: u8) -> RResult<()> {
+        let penalty = U8::from(penalty_perc);
+        if penalty < self.min_penalty_perc.get() || penalty > self.max_penalty_perc.get() {
+            return Err(BitsaveErrors::InvalidPenalty(InvalidPenalty { penalty_perc }).into());
+        }
 
-        let amount_of_saving = amount_received - saving_fee;
-        // Update pool
+        let now = U256::from(block::timestamp());
+        if maturity_time <= now {
+            return Err(BitsaveErrors::MaturityInPast(MaturityInPast { maturity_time }).into());
+        }
 
-        let token_id = Address::ZERO; // todo: fix in token address
+        let lock_duration = maturity_time - now;
+        let min_lock_duration = self.min_lock_duration.get();
+        if lock_duration < min_lock_duration {
+            return Err(BitsaveErrors::LockTooShort(LockTooShort { min_lock_duration }).into());
+        }
+        let max_lock_duration = self.max_lock_duration.get();
+        if lock_duration > max_lock_duration {
+            return Err(BitsaveErrors::LockTooLong(LockTooLong { max_lock_duration }).into());
+        }
 
-        // TODO: add safe mode fn;
-        if use_safe_mode {
-            return Err(BitsaveErrors::NotSupported(NotSupported {}).into());
+        let max_active_savings = self.max_active_savings.get();
+        if self.users_mapping.get(msg::sender()).savings_count.get() >= max_active_savings {
+            return Err(BitsaveErrors::TooManySavings(TooManySavings {
+                max_active_savings: max_active_savings.to::<u8>(),
+            })
+            .into());
         }
 
+        Ok(())
+    }
+
+    /// Store a new saving for the sender once funds are in the contract,
+    /// returns the token and amount saved after any safe mode swap
+    fn save(
+        &mut self,
+        name_of_saving: String,
+        maturity_time: U256,
+        penalty_perc: u8,
+        use_safe_mode: bool,
+        token_id: Address,
+        amount_of_saving: U256,
+        min_amount_out: U256,
+        goal_amount: U256,
+        unlock_on_goal: bool,
+    ) -> RResult<(Address, U256)> {
+        self.validate_saving_terms(maturity_time, penalty_perc)?;
+
+        // safe mode savings are held in stablecoin
+        let (token_id, amount_of_saving) = if use_safe_mode {
+            let stable_amount = self.swap_to_stablecoin(token_id, amount_of_saving, min_amount_out)?;
+            (self.stablecoin_address.get(), stable_amount)
+        } else {
+            (token_id, amount_of_saving)
+        };
+
+        self.sync_vault_state()?;
+        self.lock_value(token_id, amount_of_saving)?;
+        let new_interest = self.calculate_interest(
+            token_id,
+            amount_of_saving,
+            U256::from(block::timestamp()),
+            maturity_time,
+        )?;
+        let pool_index = self.pool_share_index.get(token_id);
+
         // user setter
         let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.accrue_pool_share(name_of_saving.clone(), pool_index);
         user_updater.create_saving_data(
-            name_of_saving,
+            name_of_saving.clone(),
             amount_of_saving,
             token_id,
             maturity_time,
             penalty_perc,
             use_safe_mode,
-            self.vault_state.get(),
-            self.total_value_locked.get(),
+            new_interest,
+            goal_amount,
+            unlock_on_goal,
         )?;
 
-        Ok(())
+        let pool_shares = self.pool_total_shares.get(token_id);
+        self.pool_total_shares.insert(token_id, pool_shares + amount_of_saving);
+
+        evm::log(SavingCreated {
+            user: msg::sender(),
+            name_of_saving: name_of_saving.clone(),
+            token_id,
+            amount: amount_of_saving,
+            maturity_time,
+            penalty_perc,
+            is_safe_mode: use_safe_mode,
+        });
+        if goal_amount > U256::ZERO {
+            evm::log(SavingGoalSet {
+                user: msg::sender(),
+                name_of_saving,
+                goal_amount,
+                unlock_on_goal,
+            });
+        }
+
+        Ok((token_id, amount_of_saving))
     }
 
-    /// Increment saving
-    pub fn increment_saving(&mut self, name_of_saving: String) -> Result<(), Vec<u8>> {
-        // retrieve some data
-        // fixme fetch user's data
+    /// Add to an existing saving of `user` once funds are in the contract
+    fn add_to_saving(
+        &mut self,
+        user: Address,
+        name_of_saving: String,
+        token_id: Address,
+        amount_to_add: U256,
+        min_amount_out: U256,
+    ) -> RResult<()> {
+        let (is_safe_mode, start_time, maturity_time) = {
+            let user_data = self.users_mapping.get(user);
+            let saving_data = user_data.savings_map.get(name_of_saving.clone());
+            (
+                saving_data.is_safe_mode.get(),
+                saving_data.start_time.get(),
+                saving_data.maturity_time.get(),
+            )
+        };
 
-        let amount_to_add = msg::value();
-        let token_id = Address::ZERO; // todo: fix in token address
+        // safe mode savings are incremented in stablecoin
+        let (token_id, amount_to_add) = if is_safe_mode {
+            let stable_amount = self.swap_to_stablecoin(token_id, amount_to_add, min_amount_out)?;
+            (self.stablecoin_address.get(), stable_amount)
+        } else {
+            (token_id, amount_to_add)
+        };
+
+        self.sync_vault_state()?;
+        self.lock_value(token_id, amount_to_add)?;
+        let new_interest =
+            self.calculate_interest(token_id, amount_to_add, start_time, maturity_time)?;
+        let pool_index = self.pool_share_index.get(token_id);
 
         // user setter
-        let mut user_updater = self.users_mapping.setter(msg::sender());
+        let mut user_updater = self.users_mapping.setter(user);
+        user_updater.accrue_pool_share(name_of_saving.clone(), pool_index);
         user_updater.increment_saving_data(
-            name_of_saving,
+            name_of_saving.clone(),
             amount_to_add,
             token_id,
+            new_interest,
+        )?;
+
+        let pool_shares = self.pool_total_shares.get(token_id);
+        self.pool_total_shares.insert(token_id, pool_shares + amount_to_add);
+
+        evm::log(SavingIncremented {
+            user,
+            name_of_saving,
+            token_id,
+            amount: amount_to_add,
+        });
+        Ok(())
+    }
+
+    /// Interest from the token's model, or the default model, in reward token units.
+    /// Models run on 18 decimal amounts so savings in any token earn alike
+    fn calculate_interest(
+        &self,
+        token_id: Address,
+        amount: U256,
+        start_time: U256,
+        end_time: U256,
+    ) -> RResult<U256> {
+        let token_model = self.token_interest_models.get(token_id);
+        let interest_model: &InterestModel = if token_model.is_set.get() {
+            &*token_model
+        } else {
+            &self.default_interest_model
+        };
+
+        let normalized_amount = math::checked_mul(amount, self.cached_token_scale(token_id))?;
+        let interest = interest_model.interest(
+            normalized_amount,
+            start_time,
+            end_time,
             self.vault_state.get(),
             self.total_value_locked.get(),
         )?;
+
+        // scale is unknown until the token was first used
+        let reward_scale = self.cached_token_scale(self.reward_token_address.get());
+        if reward_scale == U256::ZERO {
+            return Ok(U256::ZERO);
+        }
+        Ok(interest / reward_scale)
+    }
+
+    /// Cached 18 decimal scale of `token_id`, zero if the token was never used
+    fn cached_token_scale(&self, token_id: Address) -> U256 {
+        if token_id == Address::ZERO {
+            U256::from(1)
+        } else {
+            self.token_scale.get(token_id)
+        }
+    }
+
+    /// Factor bringing `token_id` amounts to 18 decimals, cached on first use
+    fn token_scale(&mut self, token_id: Address) -> RResult<U256> {
+        if token_id == Address::ZERO {
+            return Ok(U256::from(1));
+        }
+
+        let cached_scale = self.token_scale.get(token_id);
+        if cached_scale > U256::ZERO {
+            return Ok(cached_scale);
+        }
+
+        let token = IERC20::new(token_id);
+        let decimals = token.decimals(Call::new_in(self))?;
+        if decimals > 18 {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+
+        let scale = U256::from(10).pow(U256::from(18 - decimals));
+        self.token_scale.insert(token_id, scale);
+        Ok(scale)
+    }
+
+    /// Count a deposit into the value locked
+    fn lock_value(&mut self, token_id: Address, amount: U256) -> RResult<()> {
+        let scale = self.token_scale(token_id)?;
+
+        let token_locked = self.token_value_locked.get(token_id);
+        self.token_value_locked.insert(token_id, token_locked + amount);
+        self.total_value_locked
+            .set(self.total_value_locked.get() + amount * scale);
+        Ok(())
+    }
+
+    /// Take a closed saving's principal out of the value locked;
+    /// saturating as `update_vault` may have overridden the total
+    fn release_value(&mut self, token_id: Address, amount: U256) {
+        let scale = self.cached_token_scale(token_id);
+
+        let token_locked = self.token_value_locked.get(token_id);
+        self.token_value_locked
+            .insert(token_id, token_locked.saturating_sub(amount));
+        self.total_value_locked
+            .set(self.total_value_locked.get().saturating_sub(amount * scale));
+    }
+
+    /// Vault state follows the reward token supply, in whole tokens,
+    /// capped at the bitsave total supply; unless overridden by an admin
+    fn sync_vault_state(&mut self) -> RResult<()> {
+        let reward_token = self.reward_token_address.get();
+        if reward_token == Address::ZERO || self.vault_state_overridden.get() {
+            return Ok(());
+        }
+
+        let scale = self.token_scale(reward_token)?;
+        let token = IERC20::new(reward_token);
+        let supply = token.total_supply(Call::new_in(self))?;
+
+        let vault_state = math::wad_mul(supply, scale)?.min(U256::from(TOTAL_SUPPLY));
+        if vault_state != self.vault_state.get() {
+            self.vault_state.set(vault_state);
+            evm::log(VaultUpdated {
+                vault_state,
+                total_value_locked: self.total_value_locked.get(),
+            });
+        }
         Ok(())
     }
 
-    /// Withdraw savings
-    pub fn withdraw_savings(&mut self, name_of_saving: String) -> Result<U256, Vec<u8>> {
-        if msg::reentrant() {
-            return Err(
-                // Should be a general error but saving size
-                BitsaveErrors::NotSupported(NotSupported {}).into());
+    /// Remove a closed saving's principal from the penalty pool shares
+    fn leave_pool(&mut self, token_id: Address, principal: U256) {
+        let pool_shares = self.pool_total_shares.get(token_id);
+        self.pool_total_shares.insert(token_id, pool_shares - principal);
+    }
+
+    /// Add a penalty to the penalty pool and share it with current savers
+    fn add_penalty_to_pool(&mut self, token_id: Address, penalty: U256) {
+        let pool_balance = self.accumulated_pool_balance.get(token_id);
+        self.accumulated_pool_balance
+            .insert(token_id, pool_balance + penalty);
+        self.distribute_to_pool(token_id, penalty);
+    }
+
+    /// Share an amount of the penalty pool between current savers of `token_id`;
+    /// held back until there are savers to share with
+    fn distribute_to_pool(&mut self, token_id: Address, amount: U256) {
+        let pool_shares = self.pool_total_shares.get(token_id);
+        let to_share = amount + self.pool_undistributed.get(token_id);
+
+        if pool_shares == U256::ZERO {
+            self.pool_undistributed.insert(token_id, to_share);
+            return;
         }
 
-        // user updater
-        let mut user_updater = self.users_mapping.setter(msg::sender());
-        let with_amount = user_updater.withdraw_saving_data(name_of_saving)?;
+        let pool_index = self.pool_share_index.get(token_id) + to_share * U256::from(WAD) / pool_shares;
+        self.pool_share_index.insert(token_id, pool_index);
+        self.pool_undistributed.insert(token_id, U256::ZERO);
+    }
+
+    /// Initialize data
+    pub fn init(&mut self) {
+        if !self.initialized.get() {
+            self.access_control.init(msg::sender());
+            self.collector_address.set(msg::sender());
+            self.join_fee.set(U256::from(MIN_BS_JOIN_FEE));
+            self.saving_fee.set(U256::from(BS_SAVING_FEE));
+            self.min_penalty_perc.set(U8::from(DEFAULT_MIN_PENALTY_PERC));
+            self.max_penalty_perc.set(U8::from(DEFAULT_MAX_PENALTY_PERC));
+            self.min_lock_duration.set(U256::from(DEFAULT_MIN_LOCK_DURATION));
+            self.max_lock_duration.set(U256::from(DEFAULT_MAX_LOCK_DURATION));
+            self.max_active_savings.set(U8::from(DEFAULT_MAX_ACTIVE_SAVINGS));
+            self.keeper_bounty_bps.set(U256::from(DEFAULT_KEEPER_BOUNTY_BPS));
+            self.general_fund.set(U256::from(0));
+            self.initialized.set(true);
+        }
+    }
+
+    pub fn change_data(
+        &mut self,
+        router_address: Address,
+        stablecoin_address: Address,
+        collector_address: Address,
+    ) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
 
-        // transfer funds
-        call(Call::new_in(self).value(with_amount), msg::sender(), &[])?;
+        if router_address == Address::ZERO
+            || stablecoin_address == Address::ZERO
+            || collector_address == Address::ZERO
+        {
+            return Err(BitsaveErrors::InvalidAddress(InvalidAddress {}).into());
+        }
+
+        self.router_address.set(router_address);
+        self.stablecoin_address.set(stablecoin_address);
+        self.collector_address.set(collector_address);
+
+        evm::log(ConfigChanged {
+            router_address,
+            stablecoin_address,
+            collector_address,
+        });
+        Ok(())
+    }
+
+    /// Override the tracked vault state and value locked; deposits and withdrawals apply
+    /// on top of the value locked, the vault state stays until `release_vault_override`
+    pub fn update_vault(&mut self, v_state: U256, total_locked: U256) -> RResult<()> {
+        self.require_role(VAULT_UPDATER_ROLE, msg::sender())?;
+
+        if v_state == U256::ZERO
+            || v_state >= U256::from(TOTAL_SUPPLY)
+            || total_locked == U256::ZERO
+        {
+            return Err(BitsaveErrors::InvalidVault(InvalidVault {}).into());
+        }
+
+        self.vault_state.set(v_state);
+        self.vault_state_overridden.set(true);
+        self.total_value_locked.set(total_locked);
+
+        evm::log(VaultUpdated {
+            vault_state: v_state,
+            total_value_locked: total_locked,
+        });
+        evm::log(VaultOverrideChanged { is_overridden: true });
+        Ok(())
+    }
+
+    /// Let the vault state follow the reward token supply again
+    pub fn release_vault_override(&mut self) -> RResult<()> {
+        self.require_role(VAULT_UPDATER_ROLE, msg::sender())?;
+
+        if !self.vault_state_overridden.get() {
+            return Err(BitsaveErrors::InvalidVault(InvalidVault {}).into());
+        }
+        self.vault_state_overridden.set(false);
+        evm::log(VaultOverrideChanged { is_overridden: false });
+
+        self.sync_vault_state()
+    }
+
+    /// Propose a new master, who has to accept it
+    pub fn propose_master(&mut self, new_master: Address) -> RResult<()> {
+        Ok(self.access_control.propose_master(new_master, msg::sender())?)
+    }
+
+    /// Accept a proposed master transfer
+    pub fn accept_master(&mut self) -> RResult<()> {
+        Ok(self.access_control.accept_master(msg::sender())?)
+    }
+
+    pub fn grant_role(&mut self, role: u8, account: Address) -> RResult<()> {
+        Ok(self.access_control.grant_role(role, account, msg::sender())?)
+    }
+
+    pub fn revoke_role(&mut self, role: u8, account: Address) -> RResult<()> {
+        Ok(self.access_control.revoke_role(role, account, msg::sender())?)
+    }
+
+    pub fn has_role(&self, role: u8, account: Address) -> bool {
+        self.access_control.has_role(role, account)
+    }
+
+    pub fn get_master(&self) -> (Address, Address) {
+        (self.access_control.master(), self.access_control.pending_master())
+    }
+
+    /// Set the interest model used when a token has none of its own;
+    /// kind is the bitsave formula, fixed apr or tiered by lock duration
+    pub fn set_default_interest_model(
+        &mut self,
+        kind: u8,
+        apr_bps: U256,
+        tier_durations: Vec<U256>,
+        tier_apr_bps: Vec<U256>,
+    ) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        self.default_interest_model
+            .set(kind, apr_bps, &tier_durations, &tier_apr_bps)?;
+
+        evm::log(InterestModelChanged {
+            token_id: Address::ZERO,
+            is_default: true,
+            kind,
+            apr_bps,
+        });
+        Ok(())
+    }
+
+    /// Set the interest model of savings in `token_id`
+    pub fn set_token_interest_model(
+        &mut self,
+        token_id: Address,
+        kind: u8,
+        apr_bps: U256,
+        tier_durations: Vec<U256>,
+        tier_apr_bps: Vec<U256>,
+    ) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        let mut token_model = self.token_interest_models.setter(token_id);
+        token_model.set(kind, apr_bps, &tier_durations, &tier_apr_bps)?;
+
+        evm::log(InterestModelChanged {
+            token_id,
+            is_default: false,
+            kind,
+            apr_bps,
+        });
+        Ok(())
+    }
+
+    /// Put `token_id` back on the default interest model
+    pub fn clear_token_interest_model(&mut self, token_id: Address) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        let mut token_model = self.token_interest_models.setter(token_id);
+        token_model.clear();
+
+        let default_model = &self.default_interest_model;
+        evm::log(InterestModelChanged {
+            token_id,
+            is_default: true,
+            kind: default_model.kind(),
+            apr_bps: default_model.apr_bps.get(),
+        });
+        Ok(())
+    }
+
+    /// Active interest model of a token: kind, apr and tiers
+    pub fn get_interest_model(&self, token_id: Address) -> (u8, U256, Vec<U256>, Vec<U256>) {
+        let token_model = self.token_interest_models.get(token_id);
+        if token_model.is_set.get() {
+            token_model.details()
+        } else {
+            self.default_interest_model.details()
+        }
+    }
+
+    /// Reward token interest a new saving of `amount` in `token_id` would earn until `maturity_time`
+    pub fn preview_interest(&self, token_id: Address, amount: U256, maturity_time: U256) -> RResult<U256> {
+        self.calculate_interest(token_id, amount, U256::from(block::timestamp()), maturity_time)
+    }
+
+    /// Set the penalty percentage and lock duration bounds for new savings;
+    /// the minimum lock stays longer than the grace window
+    pub fn set_saving_limits(
+        &mut self,
+        min_penalty_perc: u8,
+        max_penalty_perc: u8,
+        min_lock_duration: U256,
+        max_lock_duration: U256,
+    ) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        if min_penalty_perc > max_penalty_perc
+            || usize::from(max_penalty_perc) > HUNDRED
+            || min_lock_duration > max_lock_duration
+            || max_lock_duration == U256::ZERO
+            || (self.grace_window.get() > U256::ZERO && self.grace_window.get() >= min_lock_duration)
+        {
+            return Err(BitsaveErrors::InvalidSavingLimits(InvalidSavingLimits {}).into());
+        }
+
+        self.min_penalty_perc.set(U8::from(min_penalty_perc));
+        self.max_penalty_perc.set(U8::from(max_penalty_perc));
+        self.min_lock_duration.set(min_lock_duration);
+        self.max_lock_duration.set(max_lock_duration);
+
+        evm::log(SavingLimitsChanged {
+            min_penalty_perc,
+            max_penalty_perc,
+            min_lock_duration,
+            max_lock_duration,
+        });
+        Ok(())
+    }
+
+    /// Set how many active savings a user can hold
+    pub fn set_max_active_savings(&mut self, max_active_savings: u8) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        if max_active_savings == 0 {
+            return Err(BitsaveErrors::InvalidSavingLimits(InvalidSavingLimits {}).into());
+        }
+
+        self.max_active_savings.set(U8::from(max_active_savings));
+        evm::log(MaxActiveSavingsChanged { max_active_savings });
+        Ok(())
+    }
+
+    pub fn get_max_active_savings(&self) -> u8 {
+        self.max_active_savings.get().to::<u8>()
+    }
+
+    /// Min and max penalty percentage, min and max lock duration
+    pub fn get_saving_limits(&self) -> (u8, u8, U256, U256) {
+        (
+            self.min_penalty_perc.get().to::<u8>(),
+            self.max_penalty_perc.get().to::<u8>(),
+            self.min_lock_duration.get(),
+            self.max_lock_duration.get(),
+        )
+    }
+
+    /// Set the penalty free grace window before maturity and the window after
+    /// maturity to claim interest in; zero claim window never expires.
+    /// The grace window is shorter than the minimum lock so no saving starts inside it
+    pub fn set_withdrawal_windows(&mut self, grace_window: U256, claim_window: U256) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        if grace_window > U256::from(MAX_GRACE_WINDOW)
+            || (grace_window > U256::ZERO && grace_window >= self.min_lock_duration.get())
+            || (claim_window > U256::ZERO && claim_window < U256::from(MIN_CLAIM_WINDOW))
+        {
+            return Err(BitsaveErrors::InvalidWithdrawalWindows(InvalidWithdrawalWindows {}).into());
+        }
+
+        self.grace_window.set(grace_window);
+        self.claim_window.set(claim_window);
+
+        evm::log(WithdrawalWindowsChanged {
+            grace_window,
+            claim_window,
+        });
+        Ok(())
+    }
+
+    /// Grace window and claim window
+    pub fn get_withdrawal_windows(&self) -> (U256, U256) {
+        (self.grace_window.get(), self.claim_window.get())
+    }
+
+    /// Current phase of a saving: 0 none, 1 locked, 2 grace, 3 matured, 4 claim window missed,
+    /// 5 unlocked by reaching its goal
+    pub fn get_saving_phase(&self, user_address: Address, name_of_saving: String) -> u8 {
+        self.users_mapping.get(user_address).saving_phase(
+            name_of_saving,
+            self.grace_window.get(),
+            self.claim_window.get(),
+        )
+    }
+
+    /// Set the join fee, flat saving fee and percentage saving fee
+    pub fn set_fees(&mut self, join_fee: U256, saving_fee: U256, saving_fee_bps: U256) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        let max_flat_fee = U256::from(MAX_FLAT_FEE);
+        if join_fee > max_flat_fee
+            || saving_fee > max_flat_fee
+            || saving_fee_bps > U256::from(MAX_SAVING_FEE_BPS)
+        {
+            return Err(BitsaveErrors::InvalidFee(InvalidFee {}).into());
+        }
+
+        self.join_fee.set(join_fee);
+        self.saving_fee.set(saving_fee);
+        self.saving_fee_bps.set(saving_fee_bps);
+
+        evm::log(FeesChanged {
+            join_fee,
+            saving_fee,
+            saving_fee_bps,
+        });
+        Ok(())
+    }
+
+    /// Split claimed fees between recipients, shares in basis points adding up to 100%;
+    /// an empty list sends everything to the collector address
+    pub fn set_fee_split(&mut self, recipients: Vec<Address>, shares_bps: Vec<U256>) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        if recipients.len() != shares_bps.len() || recipients.len() > MAX_FEE_RECIPIENTS {
+            return Err(BitsaveErrors::InvalidFee(InvalidFee {}).into());
+        }
+        let bps_divisor = U256::from(BPS_DIVISOR);
+        if shares_bps.iter().any(|share| *share > bps_divisor) {
+            return Err(BitsaveErrors::InvalidFee(InvalidFee {}).into());
+        }
+        let total_bps = shares_bps
+            .iter()
+            .try_fold(U256::ZERO, |total, share| math::checked_add(total, *share))?;
+        if !recipients.is_empty() && total_bps != bps_divisor {
+            return Err(BitsaveErrors::InvalidFee(InvalidFee {}).into());
+        }
+        if recipients.contains(&Address::ZERO) {
+            return Err(BitsaveErrors::InvalidAddress(InvalidAddress {}).into());
+        }
+
+        self.fee_recipients.erase();
+        self.fee_recipient_bps.erase();
+        for (recipient, share) in recipients.iter().zip(shares_bps.iter()) {
+            self.fee_recipients.push(*recipient);
+            self.fee_recipient_bps.push(*share);
+        }
+
+        evm::log(FeeSplitChanged {
+            recipients,
+            shares_bps,
+        });
+        Ok(())
+    }
+
+    /// Pay out the accrued fees of a token to the fee split, or the collector;
+    /// open to the collector and fee collector role holders
+    pub fn claim_fees(&mut self, token_id: Address) -> RResult<U256> {
+        self.lock()?;
+        if msg::sender() != self.collector_address.get() {
+            self.require_role(FEE_COLLECTOR_ROLE, msg::sender())?;
+        }
+
+        let fees = self.unclaimed_fees.get(token_id);
+        if fees == U256::ZERO {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+        self.unclaimed_fees.insert(token_id, U256::ZERO);
+
+        let recipients_count = self.fee_recipients.len();
+        if recipients_count == 0 {
+            let collector = self.collector_address.get();
+            self.send_funds(token_id, collector, fees)?;
+        } else {
+            let mut paid = U256::ZERO;
+            for i in 0..recipients_count {
+                let recipient = self.fee_recipients.get(i).unwrap_or_default();
+                // last recipient takes the rounding dust
+                let share = if i == recipients_count - 1 {
+                    fees - paid
+                } else {
+                    math::mul_div(
+                        fees,
+                        self.fee_recipient_bps.get(i).unwrap_or_default(),
+                        U256::from(BPS_DIVISOR),
+                    )?
+                };
+                paid += share;
+                self.send_funds(token_id, recipient, share)?;
+            }
+        }
+
+        evm::log(FeesClaimed {
+            token_id,
+            claimer: msg::sender(),
+            amount: fees,
+        });
+        self.unlock();
+        Ok(fees)
+    }
+
+    /// Fees of a token waiting to be claimed
+    pub fn get_unclaimed_fees(&self, token_id: Address) -> U256 {
+        self.unclaimed_fees.get(token_id)
+    }
+
+    /// Fee split recipients and their shares in basis points
+    pub fn get_fee_split(&self) -> (Vec<Address>, Vec<U256>) {
+        let mut recipients = Vec::with_capacity(self.fee_recipients.len());
+        let mut shares_bps = Vec::with_capacity(self.fee_recipients.len());
+        for i in 0..self.fee_recipients.len() {
+            recipients.push(self.fee_recipients.get(i).unwrap_or_default());
+            shares_bps.push(self.fee_recipient_bps.get(i).unwrap_or_default());
+        }
+        (recipients, shares_bps)
+    }
+
+    /// Set the keeper bounty taken out of each recurring deposit, in basis points
+    pub fn set_keeper_bounty(&mut self, keeper_bounty_bps: U256) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        if keeper_bounty_bps > U256::from(MAX_KEEPER_BOUNTY_BPS) {
+            return Err(BitsaveErrors::InvalidFee(InvalidFee {}).into());
+        }
+
+        self.keeper_bounty_bps.set(keeper_bounty_bps);
+        evm::log(KeeperBountyChanged { keeper_bounty_bps });
+        Ok(())
+    }
+
+    pub fn get_keeper_bounty(&self) -> U256 {
+        self.keeper_bounty_bps.get()
+    }
+
+    /// Join fee, flat saving fee and percentage saving fee in basis points
+    pub fn get_fees(&self) -> (U256, U256, U256) {
+        (self.join_fee.get(), self.saving_fee.get(), self.saving_fee_bps.get())
+    }
+
+    /// Fees for a user saving `amount` of `token_id`:
+    /// join fee (zero for members), flat fee, percentage fee and the `msg::value` to send
+    /// with `create_saving`/`create_token_saving`, or with `join_and_create_saving`
+    /// for a native saving of a non member
+    pub fn quote_fees(&self, user_address: Address, token_id: Address, amount: U256) -> (U256, U256, U256, U256) {
+        let user_data = self.users_mapping.get(user_address);
+        let join_fee = if user_data.user_exists.get() {
+            U256::ZERO
+        } else {
+            self.join_fee.get()
+        };
+
+        // a fee credit waives the saving fee
+        let (flat_fee, perc_fee) = if user_data.fee_credits.get() > U256::ZERO {
+            (U256::ZERO, U256::ZERO)
+        } else {
+            (
+                self.saving_fee.get(),
+                amount * self.saving_fee_bps.get() / U256::from(BPS_DIVISOR),
+            )
+        };
+
+        let msg_value = if token_id == Address::ZERO {
+            join_fee + amount + flat_fee + perc_fee
+        } else {
+            flat_fee
+        };
+
+        (join_fee, flat_fee, perc_fee, msg_value)
+    }
+
+    /// Halt joining and saving
+    pub fn pause(&mut self) -> RResult<()> {
+        self.require_role(PAUSER_ROLE, msg::sender())?;
+
+        self.set_paused();
+        evm::log(Paused { guardian: msg::sender() });
+        Ok(())
+    }
+
+    /// Resume joining and saving, ends emergency mode
+    pub fn unpause(&mut self) -> RResult<()> {
+        self.require_role(PAUSER_ROLE, msg::sender())?;
+
+        if self.paused.get() {
+            self.paused_time.set(self.total_paused_time());
+            self.paused.set(false);
+        }
+        if self.emergency_mode.get() {
+            self.emergency_mode.set(false);
+            evm::log(EmergencyModeChanged {
+                guardian: msg::sender(),
+                enabled: false,
+            });
+        }
+        evm::log(Unpaused { guardian: msg::sender() });
+        Ok(())
+    }
+
+    /// Emergency mode pauses the contract and opens `emergency_withdraw`
+    pub fn set_emergency_mode(&mut self, enabled: bool) -> RResult<()> {
+        self.require_role(PAUSER_ROLE, msg::sender())?;
+
+        if enabled && !self.paused.get() {
+            self.set_paused();
+            evm::log(Paused { guardian: msg::sender() });
+        }
+        self.emergency_mode.set(enabled);
+        evm::log(EmergencyModeChanged {
+            guardian: msg::sender(),
+            enabled,
+        });
+        Ok(())
+    }
+
+    /// Paused and emergency mode flags
+    pub fn get_pause_state(&self) -> (bool, bool) {
+        (self.paused.get(), self.emergency_mode.get())
+    }
+
+    /// Set the token interest is paid in
+    pub fn set_reward_token(&mut self, reward_token: Address) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        if reward_token == Address::ZERO {
+            return Err(BitsaveErrors::InvalidAddress(InvalidAddress {}).into());
+        }
+
+        // pool belongs to the old token
+        if self.reward_pool_balance.get() > U256::ZERO {
+            return Err(BitsaveErrors::NotSupported(NotSupported {}).into());
+        }
+
+        // interest is converted to the reward token's decimals
+        self.token_scale(reward_token)?;
+        self.reward_token_address.set(reward_token);
+        evm::log(RewardTokenChanged { reward_token });
+        Ok(())
+    }
+
+    /// Fund the reward pool interest is paid from
+    pub fn fund_reward_pool(&mut self, amount: U256) -> RResult<U256> {
+        self.lock()?;
+
+        let reward_token = self.reward_token_address.get();
+        if reward_token == Address::ZERO {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+
+        let received = self.receive_token(reward_token, msg::sender(), amount)?;
+
+        let new_balance = self.reward_pool_balance.get() + received;
+        self.reward_pool_balance.set(new_balance);
+        self.unlock();
+        Ok(new_balance)
+    }
+
+    /// Claim interest the reward pool couldn't cover at withdrawal
+    pub fn claim_interest(&mut self) -> RResult<U256> {
+        self.lock()?;
+        self.record_activity();
+
+        let reward_token = self.reward_token_address.get();
+        let pending = self.users_mapping.get(msg::sender()).pending_interest.get();
+        let interest_paid = pending.min(self.reward_pool_balance.get());
+        if reward_token == Address::ZERO || interest_paid == U256::ZERO {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+
+        self.reward_pool_balance
+            .set(self.reward_pool_balance.get() - interest_paid);
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.pending_interest.set(pending - interest_paid);
+
+        self.send_funds(reward_token, msg::sender(), interest_paid)?;
+        self.unlock();
+        Ok(interest_paid)
+    }
+
+    /// Pending penalty pool share of a user's saving, paid out at maturity
+    pub fn get_pool_share(&self, user_address: Address, name_of_saving: String) -> U256 {
+        let user_data = self.users_mapping.get(user_address);
+        let saving_data = user_data.savings_map.get(name_of_saving);
+        if !saving_data.is_valid.get() {
+            return U256::ZERO;
+        }
+
+        let pool_index = self.pool_share_index.get(saving_data.token_id.get());
+        saving_data.pool_reward.get()
+            + saving_data.amount.get() * (pool_index - saving_data.pool_index.get()) / U256::from(WAD)
+    }
+
+    /// Set point redemption rates; zero disables that redemption.
+    /// `reward_rate` is 1e18 scaled reward tokens per point, capped at one to one
+    pub fn set_points_rates(&mut self, reward_rate: U256, points_per_fee_credit: U256) -> RResult<()> {
+        self.require_role(CONFIG_ADMIN_ROLE, msg::sender())?;
+
+        if reward_rate > U256::from(MAX_POINTS_REWARD_RATE) {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+
+        self.points_reward_rate.set(reward_rate);
+        self.points_per_fee_credit.set(points_per_fee_credit);
+        evm::log(PointsRatesChanged {
+            reward_rate,
+            points_per_fee_credit,
+        });
+        Ok(())
+    }
+
+    /// Convert points into reward tokens from the reward pool
+    pub fn redeem_points_for_reward(&mut self, points: U256) -> RResult<U256> {
+        self.lock()?;
+        self.record_activity();
+
+        let reward_token = self.reward_token_address.get();
+        let reward_amount = math::wad_mul(points, self.points_reward_rate.get())?;
+        let pool_balance = self.reward_pool_balance.get();
+        if reward_token == Address::ZERO || reward_amount == U256::ZERO || reward_amount > pool_balance {
+            return Err(BitsaveErrors::NotSupported(NotSupported {}).into());
+        }
+
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.spend_points(points)?;
+        self.reward_pool_balance.set(pool_balance - reward_amount);
+
+        evm::log(PointsRedeemed {
+            user: msg::sender(),
+            points,
+            reward_amount,
+            fee_credits: U256::ZERO,
+        });
+
+        self.send_funds(reward_token, msg::sender(), reward_amount)?;
+        self.unlock();
+        Ok(reward_amount)
+    }
+
+    /// Spend points on savings that skip the saving fee
+    pub fn redeem_points_for_fee_credits(&mut self, fee_credits: U256) -> RResult<U256> {
+        self.record_activity();
+
+        let points_per_credit = self.points_per_fee_credit.get();
+        if points_per_credit == U256::ZERO
+            || fee_credits == U256::ZERO
+            || fee_credits > U256::from(MAX_FEE_CREDITS_PER_REDEEM)
+        {
+            return Err(BitsaveErrors::NotSupported(NotSupported {}).into());
+        }
+
+        let points = math::checked_mul(fee_credits, points_per_credit)?;
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.spend_points(points)?;
+        let total_credits = math::checked_add(user_updater.fee_credits.get(), fee_credits)?;
+        user_updater.fee_credits.set(total_credits);
+
+        evm::log(PointsRedeemed {
+            user: msg::sender(),
+            points,
+            reward_amount: U256::ZERO,
+            fee_credits,
+        });
+
+        Ok(total_credits)
+    }
+
+    /// My remaining fee credits
+    pub fn get_user_fee_credits(&self) -> U256 {
+        self.users_mapping.get(msg::sender()).fee_credits.get()
+    }
+
+    /// My gathered points
+    pub fn get_user_points(&self) -> BResult<U256> {
+        Ok(self.users_mapping.get(msg::sender()).total_point.get())
+    }
+
+    /// My points still locked in active savings
+    pub fn get_user_locked_points(&self) -> U256 {
+        self.users_mapping.get(msg::sender()).locked_points.get()
+    }
+
+    /// Address of the member holding `user_name`
+    pub fn get_user_address(&self, user_name: String) -> RResult<Address> {
+        let user_address = self.user_names.get(user_name);
+        if user_address == Address::ZERO {
+            return Err(BitsaveErrors::InvalidUser(InvalidUser {}).into());
+        }
+        Ok(user_address)
+    }
+
+    /// User name, id and address of the member holding `user_name`
+    pub fn get_user_details_by_name(&self, user_name: String) -> RResult<(String, U256, Address)> {
+        let user_address = self.get_user_address(user_name)?;
+        self.get_user_details(user_address)
+    }
+
+    /// User name, id and address of a member
+    pub fn get_user_details(&self, user_address: Address) -> RResult<(String, U256, Address)> {
+        let user_data = self.users_mapping.get(user_address);
+        if !user_data.user_exists.get() {
+            return Err(BitsaveErrors::InvalidUser(InvalidUser {}).into());
+        }
+
+        Ok((
+            user_data.user_name.get_string(),
+            user_data.get_user_id(),
+            user_data.user_address.get(),
+        ))
+    }
+
+    /// Full saving data: is_valid, amount, maturity_time, start_time,
+    /// token_id, is_safe_mode, interest_accumulated, penalty_perc
+    pub fn get_saving(
+        &self,
+        user_address: Address,
+        name_of_saving: String,
+    ) -> (bool, U256, U256, U256, Address, bool, U256, u8) {
+        let user_data = self.users_mapping.get(user_address);
+        let saving_data = user_data.savings_map.get(name_of_saving);
+
+        (
+            saving_data.is_valid.get(),
+            saving_data.amount.get(),
+            saving_data.maturity_time.get(),
+            saving_data.start_time.get(),
+            saving_data.token_id.get(),
+            saving_data.is_safe_mode.get(),
+            saving_data.interest_accumulated.get(),
+            saving_data.penalty_perc.get().to::<u8>(),
+        )
+    }
+
+    /// Saving amount, goal and percent of the goal reached (capped at 100); zero goal for none
+    pub fn get_saving_progress(&self, user_address: Address, name_of_saving: String) -> (U256, U256, U256) {
+        self.users_mapping.get(user_address).goal_progress(name_of_saving)
+    }
+
+    /// Page of a user's active saving names, with the total number of them
+    pub fn get_active_saving_names(
+        &self,
+        user_address: Address,
+        offset: U256,
+        limit: U256,
+    ) -> (Vec<String>, U256) {
+        self.users_mapping.get(user_address).saving_names(false, offset, limit)
+    }
+
+    /// Page of a user's closed saving names, with the total number of them
+    pub fn get_closed_saving_names(
+        &self,
+        user_address: Address,
+        offset: U256,
+        limit: U256,
+    ) -> (Vec<String>, U256) {
+        self.users_mapping.get(user_address).saving_names(true, offset, limit)
+    }
+
+    /// Number of a user's active savings
+    pub fn get_savings_count(&self, user_address: Address) -> u8 {
+        self.users_mapping.get(user_address).savings_count.get().to::<u8>()
+    }
+
+    pub fn get_bitsave_user_count(&self) -> U256 {
+        self.user_count.get()
+    }
+
+    pub fn get_total_value_locked(&self) -> U256 {
+        self.total_value_locked.get()
+    }
+
+    pub fn get_vault_state(&self) -> U256 {
+        self.vault_state.get()
+    }
+
+    /// Value locked in a single token, in the token's own decimals
+    pub fn get_token_value_locked(&self, token_id: Address) -> U256 {
+        self.token_value_locked.get(token_id)
+    }
+
+    /// Penalty pool held for savers of `token_id`
+    pub fn get_accumulated_pool(&self, token_id: Address) -> U256 {
+        self.accumulated_pool_balance.get(token_id)
+    }
+
+    pub fn get_reward_pool_balance(&self) -> U256 {
+        self.reward_pool_balance.get()
+    }
+
+    pub fn get_general_fund(&self) -> U256 {
+        self.general_fund.get()
+    }
+
+    /// Native balance held by the contract
+    pub fn get_bitsave_balance(&self) -> U256 {
+        contract::balance()
+    }
+
+    /// Balance of an erc20 token held by the contract
+    pub fn get_tokens_balance(&self, token_id: Address) -> RResult<U256> {
+        let token = IERC20::new(token_id);
+        Ok(token.balance_of(self, contract::address())?)
+    }
+
+    /// Join the space
+    #[payable]
+    pub fn join_bitsave(&mut self, user_name: String) -> RResult<Address> {
+        self.lock()?;
+        self.require_not_paused()?;
+
+        // check for joining fee
+        if msg::value() < self.join_fee.get() {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+        self.accrue_fee(Address::ZERO, msg::value());
+
+        let user_address = self.register_user(user_name)?;
+
+        self.unlock();
+        // return user exists txn
+        Ok(user_address)
+    }
+
+    /// Change the sender's user name, the old name is freed for others
+    pub fn rename_user(&mut self, new_name: String) -> RResult<()> {
+        self.require_not_paused()?;
+        self.require_member(msg::sender())?;
+        self.record_activity();
+
+        self.claim_user_name(new_name.clone(), msg::sender())?;
+        let old_name = self.users_mapping.get(msg::sender()).user_name.get_string();
+        self.user_names.insert(old_name.clone(), Address::ZERO);
+
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.user_name.set_str(new_name.clone());
+
+        evm::log(UserRenamed {
+            user: msg::sender(),
+            old_name,
+            new_name,
+        });
+        Ok(())
+    }
+
+    /// Join bitsave and create a native saving in one transaction;
+    /// `msg::value` covers the join fee, the saving fee and the saving, goal as in `create_saving`.
+    /// Returns the new member's address, the saving's token, amount and maturity
+    #[payable]
+    pub fn join_and_create_saving(
+        &mut self,
+        user_name: String,
+        name_of_saving: String,
+        maturity_time: U256,
+        penalty_perc: u8,
+        use_safe_mode: bool,
+        min_amount_out: U256,
+        goal_amount: U256,
+        unlock_on_goal: bool,
+    ) -> RResult<(Address, Address, U256, U256)> {
+        self.lock()?;
+        self.require_not_paused()?;
+
+        let join_fee = self.join_fee.get();
+        if msg::value() < join_fee {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+        self.accrue_fee(Address::ZERO, join_fee);
+
+        let user_address = self.register_user(user_name)?;
+
+        let saving_value = msg::value() - join_fee;
+        let amount_of_saving = self.collect_saving_fee(Address::ZERO, saving_value, saving_value)?;
+
+        let (token_id, amount_of_saving) = self.save(
+            name_of_saving,
+            maturity_time,
+            penalty_perc,
+            use_safe_mode,
+            Address::ZERO,
+            amount_of_saving,
+            min_amount_out,
+            goal_amount,
+            unlock_on_goal,
+        )?;
+
+        self.unlock();
+        Ok((user_address, token_id, amount_of_saving, maturity_time))
+    }
+
+    /// Create a new saving; `min_amount_out` bounds the stablecoin received in safe mode.
+    /// A nonzero `goal_amount` is saved toward, with `unlock_on_goal` the saving can be
+    /// withdrawn without penalty once it's reached, interest is only paid at maturity
+    #[payable]
+    pub fn create_saving(
+        &mut self,
+        name_of_saving: String,
+        maturity_time: U256,
+        penalty_perc: u8,
+        use_safe_mode: bool,
+        min_amount_out: U256,
+        goal_amount: U256,
+        unlock_on_goal: bool,
+    ) -> RResult<()> {
+        self.lock()?;
+        self.require_not_paused()?;
+        self.require_member(msg::sender())?;
+        self.record_activity();
+
+        let token_id = Address::ZERO;
+        let amount_of_saving = self.collect_saving_fee(token_id, msg::value(), msg::value())?;
+
+        self.save(
+            name_of_saving,
+            maturity_time,
+            penalty_perc,
+            use_safe_mode,
+            token_id,
+            amount_of_saving,
+            min_amount_out,
+            goal_amount,
+            unlock_on_goal,
+        )?;
+
+        self.unlock();
+        Ok(())
+    }
+
+    /// Create a new saving in an erc20 token;
+    /// flat fee is paid in native token, `amount` is pulled with `transferFrom`
+    /// and the percentage fee taken from it. Goal as in `create_saving`
+    #[payable]
+    pub fn create_token_saving(
+        &mut self,
+        name_of_saving: String,
+        maturity_time: U256,
+        penalty_perc: u8,
+        use_safe_mode: bool,
+        token_id: Address,
+        amount: U256,
+        min_amount_out: U256,
+        goal_amount: U256,
+        unlock_on_goal: bool,
+    ) -> RResult<()> {
+        self.lock()?;
+        self.require_not_paused()?;
+        self.require_member(msg::sender())?;
+        self.record_activity();
+
+        if token_id == Address::ZERO {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+        if amount == U256::ZERO {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+
+        let received = self.receive_token(token_id, msg::sender(), amount)?;
+        let amount_of_saving = self.collect_saving_fee(token_id, msg::value(), received)?;
+
+        self.save(
+            name_of_saving,
+            maturity_time,
+            penalty_perc,
+            use_safe_mode,
+            token_id,
+            amount_of_saving,
+            min_amount_out,
+            goal_amount,
+            unlock_on_goal,
+        )?;
+
+        self.unlock();
+        Ok(())
+    }
+
+    /// Increment saving
+    #[payable]
+    pub fn increment_saving(&mut self, name_of_saving: String, min_amount_out: U256) -> Result<(), Vec<u8>> {
+        self.lock()?;
+        self.require_not_paused()?;
+        self.require_member(msg::sender())?;
+        self.record_activity();
+
+        let amount_to_add = msg::value();
+        let token_id = Address::ZERO;
+
+        self.add_to_saving(msg::sender(), name_of_saving, token_id, amount_to_add, min_amount_out)?;
+
+        self.unlock();
+        Ok(())
+    }
+
+    /// Increment an erc20 saving, `amount` is pulled with `transferFrom`
+    pub fn increment_token_saving(
+        &mut self,
+        name_of_saving: String,
+        token_id: Address,
+        amount: U256,
+        min_amount_out: U256,
+    ) -> RResult<()> {
+        self.lock()?;
+        self.require_not_paused()?;
+        self.require_member(msg::sender())?;
+        self.record_activity();
+
+        if token_id == Address::ZERO {
+            return Err(BitsaveErrors::InvalidToken(InvalidToken {}).into());
+        }
+        if amount == U256::ZERO {
+            return Err(BitsaveErrors::InvalidPrice(InvalidPrice {}).into());
+        }
+
+        let received = self.receive_token(token_id, msg::sender(), amount)?;
+
+        self.add_to_saving(msg::sender(), name_of_saving, token_id, received, min_amount_out)?;
+
+        self.unlock();
+        Ok(())
+    }
+
+    /// Raise a saving's goal, or give one to a saving created without;
+    /// whether reaching it unlocks the saving can only be chosen at creation
+    pub fn raise_saving_goal(&mut self, name_of_saving: String, goal_amount: U256) -> RResult<()> {
+        self.require_not_paused()?;
+        self.record_activity();
+
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        let unlock_on_goal = user_updater.raise_goal(name_of_saving.clone(), goal_amount)?;
+
+        evm::log(SavingGoalSet {
+            user: msg::sender(),
+            name_of_saving,
+            goal_amount,
+            unlock_on_goal,
+        });
+        Ok(())
+    }
+
+    /// Deposit `amount` of `token_id` into a saving every `interval` until `end_time`,
+    /// pulled from the sender's allowance by any keeper once due;
+    /// `min_amount_out` bounds each deposit's stablecoin in safe mode
+    pub fn schedule_recurring_deposit(
+        &mut self,
+        name_of_saving: String,
+        token_id: Address,
+        amount: U256,
+        min_amount_out: U256,
+        interval: U256,
+        end_time: U256,
+    ) -> RResult<()> {
+        self.require_not_paused()?;
+        self.require_member(msg::sender())?;
+        self.record_activity();
+        self.check_recurring_terms(msg::sender(), name_of_saving.clone(), token_id, end_time)?;
+
+        let now = U256::from(block::timestamp());
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        let mut saving_updater = user_updater.savings_map.setter(name_of_saving.clone());
+        saving_updater
+            .recurring
+            .schedule(token_id, amount, min_amount_out, interval, end_time, now)?;
+
+        evm::log(RecurringDepositScheduled {
+            user: msg::sender(),
+            name_of_saving,
+            token_id,
+            amount,
+            interval,
+            end_time,
+        });
+        Ok(())
+    }
+
+    /// Change the amount, interval and end of a saving's recurring deposit,
+    /// the next deposit is due one interval from now
+    pub fn modify_recurring_deposit(
+        &mut self,
+        name_of_saving: String,
+        amount: U256,
+        min_amount_out: U256,
+        interval: U256,
+        end_time: U256,
+    ) -> RResult<()> {
+        self.require_not_paused()?;
+        self.record_activity();
+
+        let (is_active, token_id) = {
+            let user_data = self.users_mapping.get(msg::sender());
+            let saving_data = user_data.savings_map.get(name_of_saving.clone());
+            (saving_data.recurring.is_active.get(), saving_data.recurring.token_id.get())
+        };
+        if !is_active {
+            return Err(BitsaveErrors::InvalidSchedule(InvalidSchedule {}).into());
+        }
+        self.check_recurring_terms(msg::sender(), name_of_saving.clone(), token_id, end_time)?;
+
+        let now = U256::from(block::timestamp());
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        let mut saving_updater = user_updater.savings_map.setter(name_of_saving.clone());
+        saving_updater
+            .recurring
+            .modify(amount, min_amount_out, interval, end_time, now)?;
+
+        evm::log(RecurringDepositScheduled {
+            user: msg::sender(),
+            name_of_saving,
+            token_id,
+            amount,
+            interval,
+            end_time,
+        });
+        Ok(())
+    }
+
+    /// Stop a saving's recurring deposit
+    pub fn cancel_recurring_deposit(&mut self, name_of_saving: String) -> RResult<()> {
+        self.record_activity();
+
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        let mut saving_updater = user_updater.savings_map.setter(name_of_saving.clone());
+        if !saving_updater.recurring.is_active.get() {
+            return Err(BitsaveErrors::InvalidSchedule(InvalidSchedule {}).into());
+        }
+        saving_updater.recurring.cancel();
+
+        evm::log(RecurringDepositCancelled {
+            user: msg::sender(),
+            name_of_saving,
+        });
+        Ok(())
+    }
+
+    /// Execute a due recurring deposit of `user`; open to anyone,
+    /// the caller earns the keeper bounty in the deposit's token. Returns the bounty paid
+    pub fn execute_recurring_deposit(
+        &mut self,
+        user: Address,
+        name_of_saving: String,
+    ) -> RResult<U256> {
+        self.lock()?;
+        self.require_not_paused()?;
+
+        let now = U256::from(block::timestamp());
+        let (token_id, amount, min_amount_out, missed) = {
+            let mut user_updater = self.users_mapping.setter(user);
+            let mut saving_updater = user_updater.savings_map.setter(name_of_saving.clone());
+            let missed = saving_updater.recurring.advance(now)?;
+            (
+                saving_updater.recurring.token_id.get(),
+                saving_updater.recurring.amount.get(),
+                saving_updater.recurring.min_amount_out.get(),
+                missed,
+            )
+        };
+
+        let received = self.receive_token(token_id, user, amount)?;
+        let bounty = received * self.keeper_bounty_bps.get() / U256::from(BPS_DIVISOR);
+        let amount_to_add = received - bounty;
+
+        self.add_to_saving(user, name_of_saving.clone(), token_id, amount_to_add, min_amount_out)?;
+        if bounty > U256::ZERO {
+            self.send_funds(token_id, msg::sender(), bounty)?;
+        }
+
+        evm::log(RecurringDepositExecuted {
+            user,
+            name_of_saving,
+            keeper: msg::sender(),
+            amount: amount_to_add,
+            bounty,
+            missed,
+        });
+
+        self.unlock();
+        Ok(bounty)
+    }
+
+    /// Recurring deposit of a saving: active, token, amount, min amount out, interval,
+    /// next execution, end time, executed and missed deposits
+    pub fn get_recurring_deposit(
+        &self,
+        user_address: Address,
+        name_of_saving: String,
+    ) -> (bool, Address, U256, U256, U256, U256, U256, U256, U256) {
+        self.users_mapping
+            .get(user_address)
+            .savings_map
+            .get(name_of_saving)
+            .recurring
+            .details()
+    }
+
+    /// Start a savings circle between bitsave members; `members` is the payout order.
+    /// Each round lasts `period` and every member puts in `contribution` of `token_id`.
+    /// Members lock `collateral_rounds` contributions on joining, each default takes one
+    /// of them less `penalty_perc` for the penalty pool; one less than the member count
+    /// covers every round
+    pub fn create_circle(
+        &mut self,
+        members: Vec<Address>,
+        token_id: Address,
+        contribution: U256,
+        period: U256,
+        penalty_perc: u8,
+        collateral_rounds: u8,
+    ) -> RResult<U256> {
+        self.require_not_paused()?;
+        self.record_activity();
+
+        if !members.contains(&msg::sender()) {
+            return Err(BitsaveErrors::InvalidCircle(InvalidCircle {}).into());
+        }
+        for member in members.iter() {
+            self.require_member(*member)?;
+        }
+
+        let penalty = U8::from(penalty_perc);
+        if penalty < self.min_penalty_perc.get() || penalty > self.max_penalty_perc.get() {
+            return Err(BitsaveErrors::InvalidPenalty(InvalidPenalty { penalty_perc }).into());
+        }
+
+        let circle_id = self.circle_count.get() + U256::from(1);
+        self.circle_count.set(circle_id);
+        let now = U256::from(block::timestamp());
+        let mut circle = self.circles.setter(circle_id);
+        circle.create(
+            token_id,
+            contribution,
+            period,
+            penalty_perc,
+            collateral_rounds,
+            &members,
+            now,
+        )?;
+
+        evm::log(CircleCreated {
+            circle_id,
+            creator: msg::sender(),
+            token_id,
+            contribution,
+            period,
+            members_count: U256::from(members.len()),
+        });
+        Ok(circle_id)
+    }
+
+    /// Join a circle by locking its collateral before the join deadline,
+    /// the first round starts once every member has joined
+    #[payable]
+    pub fn join_circle(&mut self, circle_id: U256) -> RResult<()> {
+        self.lock()?;
+        self.require_not_paused()?;
+        self.record_activity();
+
+        let (token_id, collateral) = {
+            let circle = self.circles.get(circle_id);
+            (circle.token_id.get(), circle.collateral_amount()?)
+        };
+        self.receive_payment(token_id, collateral)?;
+
+        let now = U256::from(block::timestamp());
+        let paused_time = self.total_paused_time();
+        let mut circle = self.circles.setter(circle_id);
+        let started = circle.join(msg::sender(), now, paused_time)?;
+
+        evm::log(CircleJoined {
+            circle_id,
+            member: msg::sender(),
+        });
+        if started {
+            evm::log(CircleStarted {
+                circle_id,
+                start_time: now,
+            });
+        }
+
+        self.unlock();
+        Ok(())
+    }
+
+    /// Leave a circle that hasn't started, e.g. once its join deadline passed
+    /// without everyone joining, and get the collateral back
+    pub fn leave_circle(&mut self, circle_id: U256) -> RResult<U256> {
+        self.lock()?;
+        self.record_activity();
+
+        let (token_id, refund) = {
+            let mut circle = self.circles.setter(circle_id);
+            let refund = circle.leave(msg::sender())?;
+            (circle.token_id.get(), refund)
+        };
+        self.send_funds(token_id, msg::sender(), refund)?;
+
+        evm::log(CircleLeft {
+            circle_id,
+            member: msg::sender(),
+            refund,
+        });
+
+        self.unlock();
+        Ok(refund)
+    }
+
+    /// Contribute to the current round of a circle
+    #[payable]
+    pub fn contribute_to_circle(&mut self, circle_id: U256) -> RResult<()> {
+        self.lock()?;
+        self.require_not_paused()?;
+        self.record_activity();
+
+        let (token_id, contribution) = {
+            let circle = self.circles.get(circle_id);
+            (circle.token_id.get(), circle.contribution.get())
+        };
+        self.receive_payment(token_id, contribution)?;
+
+        let now = U256::from(block::timestamp());
+        let paused_time = self.total_paused_time();
+        let mut circle = self.circles.setter(circle_id);
+        let round = circle.contribute(msg::sender(), now, paused_time)?;
+
+        evm::log(CircleContribution {
+            circle_id,
+            member: msg::sender(),
+            round,
+            amount: contribution,
+        });
+
+        self.unlock();
+        Ok(())
+    }
+
+    /// Close a circle's round once everyone contributed or its period is over and credit
+    /// the pot to the member whose turn it is, to take with `claim_circle_funds`; open to
+    /// anyone. Rounds are pushed back by any pause so members aren't counted as defaulting
+    /// while they can't contribute. Returns the payout
+    pub fn close_circle_round(&mut self, circle_id: U256) -> RResult<U256> {
+        self.lock()?;
+        self.require_not_paused()?;
+
+        let now = U256::from(block::timestamp());
+        let paused_time = self.total_paused_time();
+        let (token_id, round_payout, is_completed) = {
+            let mut circle = self.circles.setter(circle_id);
+            let round_payout = circle.close_round(now, paused_time)?;
+            (circle.token_id.get(), round_payout, circle.is_completed.get())
+        };
+
+        for (member, penalty) in round_payout.defaulters.iter() {
+            evm::log(CircleDefault {
+                circle_id,
+                member: *member,
+                round: round_payout.round,
+                penalty: *penalty,
+            });
+        }
+        if round_payout.penalty > U256::ZERO {
+            self.add_penalty_to_pool(token_id, round_payout.penalty);
+        }
+
+        evm::log(CirclePayout {
+            circle_id,
+            recipient: round_payout.recipient,
+            round: round_payout.round,
+            amount: round_payout.payout,
+        });
+        // last round, the collateral left is claimable too
+        if is_completed {
+            evm::log(CircleCompleted { circle_id });
+        }
+
+        self.unlock();
+        Ok(round_payout.payout)
+    }
+
+    /// Take the sender's collateral, claimable funds and contribution to the current
+    /// round out of a circle while in emergency mode
+    pub fn emergency_leave_circle(&mut self, circle_id: U256) -> RResult<U256> {
+        self.lock()?;
+        self.record_activity();
+
+        if !self.emergency_mode.get() {
+            return Err(BitsaveErrors::NotSupported(NotSupported {}).into());
+        }
+
+        let (token_id, refund) = {
+            let mut circle = self.circles.setter(circle_id);
+            let refund = circle.emergency_refund(msg::sender())?;
+            (circle.token_id.get(), refund)
+        };
+        self.send_funds(token_id, msg::sender(), refund)?;
+
+        evm::log(CircleLeft {
+            circle_id,
+            member: msg::sender(),
+            refund,
+        });
+
+        self.unlock();
+        Ok(refund)
+    }
+
+    /// Withdraw the sender's circle payouts and released collateral
+    pub fn claim_circle_funds(&mut self, circle_id: U256) -> RResult<U256> {
+        self.lock()?;
+        self.record_activity();
+
+        let (token_id, amount) = {
+            let mut circle = self.circles.setter(circle_id);
+            let amount = circle.claim(msg::sender())?;
+            (circle.token_id.get(), amount)
+        };
+        self.send_funds(token_id, msg::sender(), amount)?;
+
+        evm::log(CircleFundsClaimed {
+            circle_id,
+            member: msg::sender(),
+            amount,
+        });
+
+        self.unlock();
+        Ok(amount)
+    }
+
+    /// Circle details: token, contribution, period, penalty, collateral rounds, members in
+    /// payout order, join deadline, start time (zero until everyone joined), current round,
+    /// current pot, completed
+    pub fn get_circle(
+        &self,
+        circle_id: U256,
+    ) -> (Address, U256, U256, u8, u8, Vec<Address>, U256, U256, U256, U256, bool) {
+        let circle = self.circles.get(circle_id);
+        (
+            circle.token_id.get(),
+            circle.contribution.get(),
+            circle.period.get(),
+            circle.penalty_perc.get().to::<u8>(),
+            circle.collateral_rounds.get().to::<u8>(),
+            circle.members(),
+            circle.join_deadline.get(),
+            circle.start_time.get(),
+            circle.current_round.get(),
+            circle.round_pot.get(),
+            circle.is_completed.get(),
+        )
+    }
+
+    /// A member of a circle: joined, collateral left,
+    /// contributed to the current round, rounds defaulted, claimable funds
+    pub fn get_circle_member(&self, circle_id: U256, member: Address) -> (bool, U256, bool, U256, U256) {
+        self.circles.get(circle_id).member_details(member)
+    }
+
+    pub fn get_circle_count(&self) -> U256 {
+        self.circle_count.get()
+    }
+
+    /// Let `beneficiary` claim all the sender's savings once the sender
+    /// has made no bitsave calls for `inactivity_period`
+    pub fn set_beneficiary(&mut self, beneficiary: Address, inactivity_period: U256) -> RResult<()> {
+        self.require_member(msg::sender())?;
+        self.record_activity();
+
+        if beneficiary == Address::ZERO
+            || beneficiary == msg::sender()
+            || inactivity_period < U256::from(MIN_INACTIVITY_PERIOD)
+            || inactivity_period > U256::from(MAX_INACTIVITY_PERIOD)
+        {
+            return Err(BitsaveErrors::InvalidBeneficiary(InvalidBeneficiary {}).into());
+        }
+
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.beneficiary.set(beneficiary);
+        user_updater.inactivity_period.set(inactivity_period);
+
+        evm::log(BeneficiarySet {
+            user: msg::sender(),
+            beneficiary,
+            inactivity_period,
+        });
+        Ok(())
+    }
+
+    /// Remove the sender's beneficiary
+    pub fn cancel_beneficiary(&mut self) -> RResult<()> {
+        self.record_activity();
+
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        if user_updater.beneficiary.get() == Address::ZERO {
+            return Err(BitsaveErrors::InvalidBeneficiary(InvalidBeneficiary {}).into());
+        }
+        user_updater.beneficiary.set(Address::ZERO);
+        user_updater.inactivity_period.set(U256::ZERO);
+
+        evm::log(BeneficiaryCancelled { user: msg::sender() });
+        Ok(())
+    }
+
+    /// Show activity without doing anything else, restarts the inactivity period
+    pub fn check_in(&mut self) -> RResult<()> {
+        self.require_member(msg::sender())?;
+        self.record_activity();
+        Ok(())
+    }
+
+    /// Beneficiary, inactivity period, last activity and when the beneficiary
+    /// can claim (zero without a beneficiary)
+    pub fn get_beneficiary(&self, user_address: Address) -> (Address, U256, U256, U256) {
+        let user_data = self.users_mapping.get(user_address);
+        let beneficiary = user_data.beneficiary.get();
+        let inactivity_period = user_data.inactivity_period.get();
+        let last_activity = user_data.last_activity.get();
+        let claimable_at = if beneficiary == Address::ZERO {
+            U256::ZERO
+        } else {
+            last_activity + inactivity_period
+        };
+        (beneficiary, inactivity_period, last_activity, claimable_at)
+    }
+
+    /// As the beneficiary of an inactive `owner`, withdraw all their active savings
+    /// without penalty; interest is paid for matured savings, what the pool can't pay
+    /// becomes the beneficiary's pending interest. Returns the number of savings claimed
+    pub fn claim_inheritance(&mut self, owner: Address) -> RResult<U256> {
+        self.lock()?;
+
+        let (names_of_savings, claimable_at) = {
+            let user_data = self.users_mapping.get(owner);
+            if user_data.beneficiary.get() != msg::sender() {
+                return Err(BitsaveErrors::Unauthorized(Unauthorized { caller: msg::sender() }).into());
+            }
+
+            let savings_count = U256::from(user_data.savings_count.get().to::<u8>());
+            let (names_of_savings, _) = user_data.saving_names(false, U256::ZERO, savings_count);
+            let claimable_at = user_data.last_activity.get() + user_data.inactivity_period.get();
+            (names_of_savings, claimable_at)
+        };
+
+        if U256::from(block::timestamp()) < claimable_at {
+            return Err(BitsaveErrors::InheritanceNotClaimable(InheritanceNotClaimable {
+                claimable_at,
+            })
+            .into());
+        }
+
+        for name_of_saving in names_of_savings.iter() {
+            let token_id = self
+                .users_mapping
+                .get(owner)
+                .savings_map
+                .get(name_of_saving.clone())
+                .token_id
+                .get();
+            let pool_index = self.pool_share_index.get(token_id);
+
+            let mut user_updater = self.users_mapping.setter(owner);
+            user_updater.accrue_pool_share(name_of_saving.clone(), pool_index);
+            let withdrawal = user_updater.inherit_saving_data(
+                name_of_saving.clone(),
+                self.grace_window.get(),
+                self.claim_window.get(),
+            )?;
+
+            self.leave_pool(token_id, withdrawal.amount);
+            self.release_value(token_id, withdrawal.amount);
+            let pool_balance = self.accumulated_pool_balance.get(token_id);
+            self.accumulated_pool_balance
+                .insert(token_id, pool_balance - withdrawal.pool_share);
+            // a share forfeited before maturity goes to the remaining savers
+            if withdrawal.forfeited_pool_share > U256::ZERO {
+                self.distribute_to_pool(token_id, withdrawal.forfeited_pool_share);
+            }
+
+            let (reward_token, interest_paid) =
+                self.take_interest_from_pool(owner, name_of_saving.clone(), withdrawal.interest);
+
+            self.send_funds(token_id, msg::sender(), withdrawal.amount + withdrawal.pool_share)?;
+            if interest_paid > U256::ZERO {
+                self.send_funds(reward_token, msg::sender(), interest_paid)?;
+            }
+
+            evm::log(SavingWithdrawn {
+                user: owner,
+                name_of_saving: name_of_saving.clone(),
+                token_id,
+                amount: withdrawal.amount,
+                penalty: U256::ZERO,
+                interest: interest_paid,
+                pool_share: withdrawal.pool_share,
+            });
+        }
+
+        // a claimed inheritance can't be claimed again; interest the pool couldn't pay,
+        // now or on earlier withdrawals, goes to the beneficiary to claim with `claim_interest`
+        let mut user_updater = self.users_mapping.setter(owner);
+        user_updater.beneficiary.set(Address::ZERO);
+        user_updater.inactivity_period.set(U256::ZERO);
+        let unpaid_interest = user_updater.pending_interest.get();
+        user_updater.pending_interest.set(U256::ZERO);
+
+        let mut beneficiary_updater = self.users_mapping.setter(msg::sender());
+        let beneficiary_pending = beneficiary_updater.pending_interest.get();
+        beneficiary_updater
+            .pending_interest
+            .set(beneficiary_pending + unpaid_interest);
+
+        let savings_count = U256::from(names_of_savings.len());
+        evm::log(InheritanceClaimed {
+            user: owner,
+            beneficiary: msg::sender(),
+            savings_count,
+        });
+
+        self.unlock();
+        Ok(savings_count)
+    }
+
+    /// Withdraw savings, returns the amount withdrawn,
+    /// the interest paid and the penalty pool share paid
+    pub fn withdraw_savings(&mut self, name_of_saving: String) -> Result<(U256, U256, U256), Vec<u8>> {
+        self.lock()?;
+        self.record_activity();
+
+        let token_id = self
+            .users_mapping
+            .get(msg::sender())
+            .savings_map
+            .get(name_of_saving.clone())
+            .token_id
+            .get();
+        let pool_index = self.pool_share_index.get(token_id);
+
+        // user updater
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.accrue_pool_share(name_of_saving.clone(), pool_index);
+        let withdrawal = user_updater.withdraw_saving_data(
+            name_of_saving.clone(),
+            self.grace_window.get(),
+            self.claim_window.get(),
+        )?;
+
+        // saving leaves the pool
+        self.leave_pool(token_id, withdrawal.amount + withdrawal.penalty);
+        self.release_value(token_id, withdrawal.amount + withdrawal.penalty);
+
+        // penalty and a share forfeited before maturity go to the remaining savers
+        let pool_share = withdrawal.pool_share;
+        let pool_balance = self.accumulated_pool_balance.get(token_id);
+        self.accumulated_pool_balance
+            .insert(token_id, pool_balance + withdrawal.penalty - pool_share);
+        let redistributed = withdrawal.penalty + withdrawal.forfeited_pool_share;
+        if redistributed > U256::ZERO {
+            self.distribute_to_pool(token_id, redistributed);
+        }
+
+        let (reward_token, interest_paid) =
+            self.take_interest_from_pool(msg::sender(), name_of_saving.clone(), withdrawal.interest);
+
+        // transfer funds in the saving's token
+        self.send_funds(token_id, msg::sender(), withdrawal.amount + pool_share)?;
+
+        // send interest
+        if interest_paid > U256::ZERO {
+            self.send_funds(reward_token, msg::sender(), interest_paid)?;
+        }
+
+        evm::log(SavingWithdrawn {
+            user: msg::sender(),
+            name_of_saving,
+            token_id,
+            amount: withdrawal.amount,
+            penalty: withdrawal.penalty,
+            interest: interest_paid,
+            pool_share,
+        });
+
+        self.unlock();
+        Ok((withdrawal.amount, interest_paid, pool_share))
+    }
+
+    /// Withdraw a saving's principal without penalty while in emergency mode;
+    /// interest is forfeited
+    pub fn emergency_withdraw(&mut self, name_of_saving: String) -> RResult<U256> {
+        self.lock()?;
+        self.record_activity();
+
+        if !self.emergency_mode.get() {
+            return Err(BitsaveErrors::NotSupported(NotSupported {}).into());
+        }
+
+        let token_id = self
+            .users_mapping
+            .get(msg::sender())
+            .savings_map
+            .get(name_of_saving.clone())
+            .token_id
+            .get();
+        let pool_index = self.pool_share_index.get(token_id);
+
+        // user updater
+        let mut user_updater = self.users_mapping.setter(msg::sender());
+        user_updater.accrue_pool_share(name_of_saving.clone(), pool_index);
+        let withdrawal = user_updater.emergency_withdraw_saving_data(name_of_saving.clone())?;
+
+        self.leave_pool(token_id, withdrawal.amount);
+        self.release_value(token_id, withdrawal.amount);
+        let pool_balance = self.accumulated_pool_balance.get(token_id);
+        self.accumulated_pool_balance
+            .insert(token_id, pool_balance - withdrawal.pool_share);
+
+        let amount = withdrawal.amount + withdrawal.pool_share;
+        self.send_funds(token_id, msg::sender(), amount)?;
+
+        evm::log(EmergencyWithdrawn {
+            user: msg::sender(),
+            name_of_saving,
+            token_id,
+            amount,
+        });
 
-        Ok(with_amount)
+        self.unlock();
+        Ok(amount)
     }
 }
@@ -0,0 +1,94 @@
+//! Checked fixed point helpers; fixed point values are scaled by `WAD` (1e18)
+
+use alloy_primitives::U256;
+use crate::constants::WAD;
+use crate::errors::{BResult, BitsaveErrors, DivisionByZero, MathOverflow};
+
+pub fn checked_mul(a: U256, b: U256) -> BResult<U256> {
+    a.checked_mul(b)
+        .ok_or(BitsaveErrors::MathOverflow(MathOverflow {}))
+}
+
+pub fn checked_add(a: U256, b: U256) -> BResult<U256> {
+    a.checked_add(b)
+        .ok_or(BitsaveErrors::MathOverflow(MathOverflow {}))
+}
+
+/// `a * b / denominator`, rounded down
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> BResult<U256> {
+    if denominator == U256::ZERO {
+        return Err(BitsaveErrors::DivisionByZero(DivisionByZero {}));
+    }
+    Ok(checked_mul(a, b)? / denominator)
+}
+
+/// Product of two fixed point values
+pub fn wad_mul(a: U256, b: U256) -> BResult<U256> {
+    mul_div(a, b, U256::from(WAD))
+}
+
+/// Quotient as a fixed point value
+pub fn wad_div(a: U256, b: U256) -> BResult<U256> {
+    mul_div(a, U256::from(WAD), b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wad(value: u64) -> U256 {
+        U256::from(value) * U256::from(WAD)
+    }
+
+    #[test]
+    fn mul_div_rejects_zero_denominator() {
+        assert!(matches!(
+            mul_div(U256::from(1), U256::from(1), U256::ZERO),
+            Err(BitsaveErrors::DivisionByZero(_))
+        ));
+        assert!(matches!(
+            wad_div(U256::from(1), U256::ZERO),
+            Err(BitsaveErrors::DivisionByZero(_))
+        ));
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert!(matches!(
+            checked_mul(U256::MAX, U256::from(2)),
+            Err(BitsaveErrors::MathOverflow(_))
+        ));
+        assert!(matches!(
+            checked_add(U256::MAX, U256::from(1)),
+            Err(BitsaveErrors::MathOverflow(_))
+        ));
+        assert!(matches!(
+            mul_div(U256::MAX, U256::from(2), U256::from(2)),
+            Err(BitsaveErrors::MathOverflow(_))
+        ));
+        assert!(matches!(
+            wad_mul(U256::MAX, wad(2)),
+            Err(BitsaveErrors::MathOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn results_round_down() {
+        assert_eq!(mul_div(U256::from(7), U256::from(3), U256::from(2)).ok(), Some(U256::from(10)));
+        assert_eq!(mul_div(U256::from(1), U256::from(1), U256::from(2)).ok(), Some(U256::ZERO));
+        // 1 / 3 in fixed point
+        assert_eq!(
+            wad_div(U256::from(1), U256::from(3)).ok(),
+            Some(U256::from(333_333_333_333_333_333u64))
+        );
+        // 1e-18 * 0.5 rounds to zero
+        assert_eq!(wad_mul(U256::from(1), wad(1) / U256::from(2)).ok(), Some(U256::ZERO));
+    }
+
+    #[test]
+    fn fixed_point_identities() {
+        assert_eq!(wad_mul(wad(3), wad(2)).ok(), Some(wad(6)));
+        assert_eq!(wad_div(wad(6), wad(2)).ok(), Some(wad(3)));
+        assert_eq!(wad_mul(U256::from(42), wad(1)).ok(), Some(U256::from(42)));
+    }
+}
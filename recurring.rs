@@ -0,0 +1,179 @@
+use alloy_primitives::{Address, U256};
+use stylus_sdk::stylus_proc::sol_storage;
+use crate::constants::MIN_RECURRING_INTERVAL;
+use crate::errors::{BResult, BitsaveErrors, InvalidSchedule, ScheduleNotDue};
+
+sol_storage! {
+    pub struct RecurringDeposit {
+        bool is_active;
+        // erc20 pulled from the saver's allowance
+        address token_id;
+        uint256 amount;
+        // least stablecoin per deposit when the saving is in safe mode
+        uint256 min_amount_out;
+        uint256 interval;
+        // when the next deposit is due, none are due after end_time
+        uint256 next_execution;
+        uint256 end_time;
+        uint256 executed_count;
+        // due deposits that passed without being executed
+        uint256 missed_count;
+    }
+}
+
+impl RecurringDeposit {
+    /// Start a schedule, the first deposit is due one interval from `now`
+    pub fn schedule(
+        &mut self,
+        token_id: Address,
+        amount: U256,
+        min_amount_out: U256,
+        interval: U256,
+        end_time: U256,
+        now: U256,
+    ) -> BResult<()> {
+        if self.is_active.get() {
+            return Err(BitsaveErrors::InvalidSchedule(InvalidSchedule {}));
+        }
+        check_terms(amount, interval, end_time, now)?;
+
+        self.is_active.set(true);
+        self.token_id.set(token_id);
+        self.amount.set(amount);
+        self.min_amount_out.set(min_amount_out);
+        self.interval.set(interval);
+        self.next_execution.set(now + interval);
+        self.end_time.set(end_time);
+        self.executed_count.set(U256::ZERO);
+        self.missed_count.set(U256::ZERO);
+        Ok(())
+    }
+
+    /// Change the terms of an active schedule, counters are kept
+    pub fn modify(
+        &mut self,
+        amount: U256,
+        min_amount_out: U256,
+        interval: U256,
+        end_time: U256,
+        now: U256,
+    ) -> BResult<()> {
+        if !self.is_active.get() {
+            return Err(BitsaveErrors::InvalidSchedule(InvalidSchedule {}));
+        }
+        check_terms(amount, interval, end_time, now)?;
+
+        self.amount.set(amount);
+        self.min_amount_out.set(min_amount_out);
+        self.interval.set(interval);
+        self.next_execution.set(now + interval);
+        self.end_time.set(end_time);
+        Ok(())
+    }
+
+    /// Stop the schedule, counters are kept for reference
+    pub fn cancel(&mut self) {
+        self.is_active.set(false);
+        self.amount.set(U256::ZERO);
+        self.min_amount_out.set(U256::ZERO);
+        self.interval.set(U256::ZERO);
+        self.next_execution.set(U256::ZERO);
+        self.end_time.set(U256::ZERO);
+    }
+
+    /// Move a due schedule to its next deposit, returns the deposits missed since the last one.
+    /// A late execution makes the latest due deposit and counts the ones before it as missed
+    pub fn advance(&mut self, now: U256) -> BResult<U256> {
+        if !self.is_active.get() {
+            return Err(BitsaveErrors::InvalidSchedule(InvalidSchedule {}));
+        }
+
+        let next_execution = self.next_execution.get();
+        if now < next_execution {
+            return Err(BitsaveErrors::ScheduleNotDue(ScheduleNotDue { next_execution }));
+        }
+
+        let end_time = self.end_time.get();
+        let (next_execution, missed) = due_deposits(next_execution, self.interval.get(), end_time, now);
+
+        self.next_execution.set(next_execution);
+        self.executed_count.set(self.executed_count.get() + U256::from(1));
+        self.missed_count.set(self.missed_count.get() + missed);
+        if next_execution > end_time {
+            // last deposit made
+            self.is_active.set(false);
+        }
+        Ok(missed)
+    }
+
+    /// Schedule details: active, token, amount, min amount out, interval, next execution,
+    /// end time, executed and missed deposits
+    pub fn details(&self) -> (bool, Address, U256, U256, U256, U256, U256, U256, U256) {
+        (
+            self.is_active.get(),
+            self.token_id.get(),
+            self.amount.get(),
+            self.min_amount_out.get(),
+            self.interval.get(),
+            self.next_execution.get(),
+            self.end_time.get(),
+            self.executed_count.get(),
+            self.missed_count.get(),
+        )
+    }
+}
+
+/// Next execution and deposits missed when a schedule due at `next_execution` is executed
+/// at `now`; the latest deposit due by `now` or `end_time` is made, the ones before it missed
+fn due_deposits(next_execution: U256, interval: U256, end_time: U256, now: U256) -> (U256, U256) {
+    let missed = (now.min(end_time) - next_execution) / interval;
+    (next_execution + (missed + U256::from(1)) * interval, missed)
+}
+
+/// A schedule needs an amount, an interval of at least the minimum and room for one deposit
+fn check_terms(amount: U256, interval: U256, end_time: U256, now: U256) -> BResult<()> {
+    if amount == U256::ZERO
+        || interval < U256::from(MIN_RECURRING_INTERVAL)
+        || end_time < now + interval
+    {
+        return Err(BitsaveErrors::InvalidSchedule(InvalidSchedule {}));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn due(next_execution: u64, interval: u64, end_time: u64, now: u64) -> (U256, U256) {
+        due_deposits(
+            U256::from(next_execution),
+            U256::from(interval),
+            U256::from(end_time),
+            U256::from(now),
+        )
+    }
+
+    #[test]
+    fn on_time_execution_misses_nothing() {
+        assert_eq!(due(100, 10, 1000, 100), (U256::from(110), U256::ZERO));
+        // any time before the following deposit is due
+        assert_eq!(due(100, 10, 1000, 109), (U256::from(110), U256::ZERO));
+    }
+
+    #[test]
+    fn late_execution_counts_missed_deposits() {
+        // due at 100, 110, 120 and 130, the one at 130 is made
+        assert_eq!(due(100, 10, 1000, 135), (U256::from(140), U256::from(3)));
+        assert_eq!(due(100, 10, 1000, 130), (U256::from(140), U256::from(3)));
+    }
+
+    #[test]
+    fn nothing_is_due_after_the_end() {
+        // due at 100, 110 and 120 only, the next one is past the end
+        assert_eq!(due(100, 10, 125, 200), (U256::from(130), U256::from(2)));
+        assert_eq!(due(100, 10, 120, 120), (U256::from(130), U256::from(2)));
+        // last deposit exactly on time
+        assert_eq!(due(120, 10, 120, 120), (U256::from(130), U256::ZERO));
+    }
+}
@@ -1,8 +1,13 @@
-use std::ops::Add;
 use alloy_primitives::{Address, U256, U8};
-use stylus_sdk::{block, stylus_proc::sol_storage};
-use crate::constants::{DIVISOR, HUNDRED, MAX_SUPPLY, TOTAL_SUPPLY, YEARS_IN_SECS};
-use crate::errors::{BResult, BitsaveErrors, GeneralError, InvalidPrice, InvalidSaving};
+use stylus_sdk::{block, evm, stylus_proc::sol_storage};
+use crate::constants::{
+    HUNDRED, PHASE_EXPIRED, PHASE_GOAL_REACHED, PHASE_GRACE, PHASE_LOCKED, PHASE_MATURED, PHASE_NONE, WAD,
+};
+use crate::errors::{
+    BResult, BitsaveErrors, GeneralError, InsufficientPoints, InvalidGoal, InvalidPrice, InvalidSaving,
+};
+use crate::events::{PointsUpdated, SavingGoalReached};
+use crate::recurring::RecurringDeposit;
 use crate::RResult;
 
 sol_storage! {
@@ -11,10 +16,23 @@ sol_storage! {
         address user_address;
         uint256 user_id;
         string user_name;
+        // active savings
         uint8 savings_count;
         mapping(string => SavingData) savings_map;
+        // active and closed saving names, a name is in at most one of them
         string[] savings_names;
+        string[] closed_savings_names;
         uint256 total_point;
+        // points of active savings, spendable once their saving completes
+        uint256 locked_points;
+        // savings that can be created without the saving fee
+        uint256 fee_credits;
+        // matured interest the reward pool couldn't cover yet
+        uint256 pending_interest;
+        // can claim all savings after inactivity_period without activity
+        address beneficiary;
+        uint256 inactivity_period;
+        uint256 last_activity;
     }
 
     pub struct SavingData {
@@ -26,9 +44,32 @@ sol_storage! {
         bool is_safe_mode;
         uint256 interest_accumulated;
         uint8 penalty_perc;
+        // penalty pool index at last checkpoint and share accrued until then
+        uint256 pool_index;
+        uint256 pool_reward;
+        // position + 1 in the active or closed names list, zero if in neither
+        uint256 name_index;
+        RecurringDeposit recurring;
+        // optional target amount, zero for none; reaching it can unlock the saving
+        uint256 goal_amount;
+        bool unlock_on_goal;
+        bool goal_reached;
     }
 }
 
+/// Outcome of closing a saving
+pub struct Withdrawal {
+    pub amount: U256,
+    /// taken off the saving for early withdrawal
+    pub penalty: U256,
+    /// penalty pool share accrued by the saving, paid once it reached maturity
+    pub pool_share: U256,
+    /// pool share given up by leaving before maturity, for the remaining savers
+    pub forfeited_pool_share: U256,
+    /// interest earned, still recorded on the saving until settled
+    pub interest: U256,
+}
+
 impl UserData {
     /// Create user details
     pub fn create_user(&mut self, address: Address, user_id: U256, user_name: String) -> bool {
@@ -44,31 +85,7 @@ impl UserData {
         self.user_id.get()
     }
 
-    /// bitsave interest calculator:
-    /// Uses bitsave formulae; to be integrated through the bitsave's token
-    fn calculate_new_interest(
-        amount: U256,
-        end_time: U256,
-        // internal data
-        vault_state: U256,
-        total_value_locked: U256
-    ) -> U256 {
-
-        let total_supply: U256 = U256::from(TOTAL_SUPPLY);
-        let max_supply: U256 = U256::from(MAX_SUPPLY);
-        let years_in_second: U256 = U256::from(YEARS_IN_SECS);
-        let hundred: U256 = U256::from(HUNDRED);
-        let divisor: U256 = U256::from(DIVISOR);
-
-
-        amount * U256::from(1) / U256::from(100);
-        let crp = ((total_supply - vault_state) / vault_state) * hundred;
-        let bs_rate = max_supply / (crp * total_value_locked);
-        let years_taken = (end_time - U256::from(block::timestamp())) / years_in_second;
-        (amount * bs_rate * years_taken) / (hundred * divisor)
-    }
-
-    fn calculate_balance_from_penalty(amount: U256, penalty_perc: U8) -> U256 {
+    pub fn calculate_balance_from_penalty(amount: U256, penalty_perc: U8) -> U256 {
         let perc_value = amount * U256::from(penalty_perc) / U256::from(100);
         amount - perc_value
     }
@@ -81,8 +98,9 @@ impl UserData {
         maturity_time: U256,
         penalty_perc: u8,
         use_safe_mode: bool,
-        vault_state: U256,
-        total_value_locked: U256
+        new_interest: U256,
+        goal_amount: U256,
+        unlock_on_goal: bool,
     ) -> BResult<()> {
         let fetched_saving = self.savings_map.get(name_of_saving.clone());
 
@@ -93,17 +111,18 @@ impl UserData {
             ));
         };
 
-        let new_interest = Self::calculate_new_interest(
-            amount_of_saving,
-            maturity_time,
-            vault_state,
-            total_value_locked
-        );
+        // a goal has to be above what's saved, unlocking needs one
+        if (goal_amount > U256::ZERO || unlock_on_goal) && goal_amount <= amount_of_saving {
+            return Err(BitsaveErrors::InvalidGoal(InvalidGoal {}));
+        }
+
+        // a reused name moves from the closed list to the active one
+        self.remove_name(name_of_saving.clone(), true);
+        self.push_name(name_of_saving.clone(), false);
+        self.savings_count.set(self.savings_count.get() + U8::from(1));
 
         let mut new_saving = self.savings_map.setter(name_of_saving);
 
-        self.total_point.add(new_interest);
-
         // update saving data
         new_saving.is_safe_mode.set(use_safe_mode);
         new_saving.is_valid.set(true);
@@ -113,6 +132,10 @@ impl UserData {
         new_saving.interest_accumulated.set(new_interest);
         new_saving.amount.set(amount_of_saving);
         new_saving.penalty_perc.set(U8::from(penalty_perc));
+        new_saving.goal_amount.set(goal_amount);
+        new_saving.unlock_on_goal.set(unlock_on_goal);
+
+        self.lock_points(new_interest);
 
         Ok(())
     }
@@ -122,8 +145,7 @@ impl UserData {
         name_of_saving: String,
         new_amount: U256,
         token_id: Address,
-        vault_state: U256,
-        total_value_locked: U256
+        new_interest: U256,
     ) -> BResult<()> {
         let saving_data = self.savings_map.get(name_of_saving.clone());
         if !saving_data.is_valid.get() {
@@ -143,15 +165,9 @@ impl UserData {
         let old_amount = saving_data.amount.get();
 
         // saving is valid, increment the saving data
-        let new_interest = Self::calculate_new_interest(
-            new_amount,
-            saving_data.maturity_time.get(),
-            vault_state,
-            total_value_locked
-        );
-        self.total_point.add(new_interest);
+        self.lock_points(new_interest);
 
-        let mut saving_updater = self.savings_map.setter(name_of_saving);
+        let mut saving_updater = self.savings_map.setter(name_of_saving.clone());
 
         // increment amount and interest
         saving_updater
@@ -159,41 +175,389 @@ impl UserData {
             .set(old_interest + new_interest);
         saving_updater.amount.set(old_amount + new_amount);
 
+        self.check_goal(name_of_saving);
+
         // saving updated
         Ok(())
     }
 
-    pub fn withdraw_saving_data(&mut self, name_of_saving: String) -> BResult<U256> {
-        let saving_data = self.savings_map.get(name_of_saving.clone());
+    /// Where a saving is in its lifecycle, given the withdrawal windows:
+    /// locked and penalized, in the penalty free grace window before maturity, matured, or
+    /// past the claim window (zero claim window never expires)
+    pub fn saving_phase(&self, name_of_saving: String, grace_window: U256, claim_window: U256) -> u8 {
+        let saving_data = self.savings_map.get(name_of_saving);
         if !saving_data.is_valid.get() {
+            return PHASE_NONE;
+        }
+
+        let now = U256::from(block::timestamp());
+        let maturity_time = saving_data.maturity_time.get();
+        if now >= maturity_time {
+            if claim_window > U256::ZERO && now >= maturity_time + claim_window {
+                return PHASE_EXPIRED;
+            }
+            return PHASE_MATURED;
+        }
+
+        if now >= maturity_time.saturating_sub(grace_window) {
+            return PHASE_GRACE;
+        }
+        if saving_data.unlock_on_goal.get() && saving_data.goal_reached.get() {
+            return PHASE_GOAL_REACHED;
+        }
+        PHASE_LOCKED
+    }
+
+    /// Clear a saving, returns what to pay out;
+    /// interest is left on the saving until `settle_interest`
+    pub fn withdraw_saving_data(
+        &mut self,
+        name_of_saving: String,
+        grace_window: U256,
+        claim_window: U256,
+    ) -> BResult<Withdrawal> {
+        let phase = self.saving_phase(name_of_saving.clone(), grace_window, claim_window);
+        if phase == PHASE_NONE {
             return Err(
                 BitsaveErrors::InvalidSaving(InvalidSaving {})
             );
         }
 
-        let mut withdraw_amount: U256 = U256::from(0);
-
-        // check if maturity is complete
+        let saving_data = self.savings_map.get(name_of_saving.clone());
+        let mut withdraw_amount: U256 = saving_data.amount.get();
+        let mut interest: U256 = U256::from(0);
+        let saving_points = saving_data.interest_accumulated.get();
+        let mut points_earned = true;
+        let pool_share = saving_data.pool_reward.get();
         let saving_amount = saving_data.amount.get();
-        if saving_data.maturity_time.get() < U256::from(block::timestamp()) {
-            // saving isn't complete, remove percentage
-            withdraw_amount =
-                Self::calculate_balance_from_penalty(saving_amount, saving_data.penalty_perc.get());
+
+        match phase {
+            PHASE_LOCKED => {
+                // saving isn't complete, remove percentage
+                withdraw_amount =
+                    Self::calculate_balance_from_penalty(saving_amount, saving_data.penalty_perc.get());
+                // points locked by the saving are forfeited
+                points_earned = false;
+            }
+            PHASE_MATURED => {
+                // saving complete, send interest
+                interest = saving_data.interest_accumulated.get();
+            }
+            PHASE_GRACE | PHASE_GOAL_REACHED => {
+                // no penalty, but interest and points are earned at maturity only
+                points_earned = false;
+            }
+            // claim window missed, principal only
+            _ => {
+                points_earned = false;
+            }
+        }
+
+        self.close_saving(name_of_saving, interest);
+        self.release_points(saving_points, points_earned);
+        let (pool_share, forfeited_pool_share) = Self::split_pool_share(phase, pool_share);
+
+        Ok(Withdrawal {
+            amount: withdraw_amount,
+            penalty: saving_amount - withdraw_amount,
+            pool_share,
+            forfeited_pool_share,
+            interest,
+        })
+    }
+
+    /// Clear a saving for the user's beneficiary, the principal is returned without penalty;
+    /// interest is paid only once matured
+    pub fn inherit_saving_data(
+        &mut self,
+        name_of_saving: String,
+        grace_window: U256,
+        claim_window: U256,
+    ) -> BResult<Withdrawal> {
+        let phase = self.saving_phase(name_of_saving.clone(), grace_window, claim_window);
+        if phase == PHASE_NONE {
+            return Err(
+                BitsaveErrors::InvalidSaving(InvalidSaving {})
+            );
+        }
+
+        let saving_data = self.savings_map.get(name_of_saving.clone());
+        let mut interest: U256 = U256::from(0);
+        let saving_points = saving_data.interest_accumulated.get();
+        let mut points_earned = true;
+        let withdrawal_amount = saving_data.amount.get();
+        let pool_share = saving_data.pool_reward.get();
+
+        match phase {
+            PHASE_MATURED => {
+                interest = saving_data.interest_accumulated.get();
+            }
+            // points are only earned along with the interest
+            _ => {
+                points_earned = false;
+            }
+        }
+
+        self.close_saving(name_of_saving, interest);
+        self.release_points(saving_points, points_earned);
+        let (pool_share, forfeited_pool_share) = Self::split_pool_share(phase, pool_share);
+
+        Ok(Withdrawal {
+            amount: withdrawal_amount,
+            penalty: U256::from(0),
+            pool_share,
+            forfeited_pool_share,
+            interest,
+        })
+    }
+
+    /// Penalty pool share paid and forfeited on leaving in `phase`;
+    /// only savers that reached maturity keep theirs
+    fn split_pool_share(phase: u8, pool_share: U256) -> (U256, U256) {
+        if phase == PHASE_MATURED || phase == PHASE_EXPIRED {
+            (pool_share, U256::from(0))
         } else {
-            // saving complete, send interest
-            withdraw_amount = saving_amount;
-            // todo: send interest
+            (U256::from(0), pool_share)
+        }
+    }
+
+    /// Clear a saving in an emergency, the full principal is returned
+    /// without penalty; interest and the points locked for it are dropped
+    pub fn emergency_withdraw_saving_data(&mut self, name_of_saving: String) -> BResult<Withdrawal> {
+        let saving_data = self.savings_map.get(name_of_saving.clone());
+        if !saving_data.is_valid.get() {
+            return Err(
+                BitsaveErrors::InvalidSaving(InvalidSaving {})
+            );
         }
 
-        // clear saving data
-        // is_valid, amount, interest_accumulated, penalty_perc
+        let saving_points = saving_data.interest_accumulated.get();
+        let withdrawal = Withdrawal {
+            amount: saving_data.amount.get(),
+            penalty: U256::from(0),
+            pool_share: saving_data.pool_reward.get(),
+            forfeited_pool_share: U256::from(0),
+            interest: U256::from(0),
+        };
+
+        self.close_saving(name_of_saving, U256::from(0));
+        self.release_points(saving_points, false);
+
+        Ok(withdrawal)
+    }
+
+    /// Clear saving data
+    /// is_valid, amount, penalty_perc, pool_reward, pool_index; interest is kept until settled
+    fn close_saving(&mut self, name_of_saving: String, interest: U256) {
+        self.remove_name(name_of_saving.clone(), false);
+        self.push_name(name_of_saving.clone(), true);
+        self.savings_count.set(self.savings_count.get() - U8::from(1));
+
         let mut saving_updater = self.savings_map.setter(name_of_saving);
 
         saving_updater.is_valid.set(false);
         saving_updater.amount.set(U256::from(0));
-        saving_updater.interest_accumulated.set(U256::from(0));
+        saving_updater.interest_accumulated.set(interest);
         saving_updater.penalty_perc.set(U8::from(0));
+        saving_updater.pool_reward.set(U256::from(0));
+        saving_updater.pool_index.set(U256::from(0));
+        saving_updater.recurring.cancel();
+        saving_updater.goal_amount.set(U256::from(0));
+        saving_updater.unlock_on_goal.set(false);
+        saving_updater.goal_reached.set(false);
+    }
+
+    /// Raise a saving's goal, or give it one; the goal has to be above what's saved and
+    /// the current goal, and can't change once reached. Whether reaching it unlocks the
+    /// saving is set at creation only. Returns that unlock mode
+    pub fn raise_goal(&mut self, name_of_saving: String, goal_amount: U256) -> BResult<bool> {
+        let saving_data = self.savings_map.get(name_of_saving.clone());
+        if !saving_data.is_valid.get() {
+            return Err(BitsaveErrors::InvalidSaving(InvalidSaving {}));
+        }
+
+        if saving_data.goal_reached.get()
+            || goal_amount <= saving_data.amount.get()
+            || goal_amount <= saving_data.goal_amount.get()
+        {
+            return Err(BitsaveErrors::InvalidGoal(InvalidGoal {}));
+        }
+        let unlock_on_goal = saving_data.unlock_on_goal.get();
+
+        let mut saving_updater = self.savings_map.setter(name_of_saving);
+        saving_updater.goal_amount.set(goal_amount);
+        Ok(unlock_on_goal)
+    }
+
+    /// Saving amount, goal and percent of the goal reached, capped at 100
+    pub fn goal_progress(&self, name_of_saving: String) -> (U256, U256, U256) {
+        let saving_data = self.savings_map.get(name_of_saving);
+        let amount = saving_data.amount.get();
+        let goal_amount = saving_data.goal_amount.get();
+        if goal_amount == U256::ZERO {
+            return (amount, goal_amount, U256::ZERO);
+        }
+
+        let hundred = U256::from(HUNDRED);
+        let percent = (amount * hundred / goal_amount).min(hundred);
+        (amount, goal_amount, percent)
+    }
+
+    /// Mark a saving's goal reached the first time its amount gets there
+    fn check_goal(&mut self, name_of_saving: String) {
+        let saving_data = self.savings_map.get(name_of_saving.clone());
+        let goal_amount = saving_data.goal_amount.get();
+        if goal_amount == U256::ZERO
+            || saving_data.goal_reached.get()
+            || saving_data.amount.get() < goal_amount
+        {
+            return;
+        }
+
+        let mut saving_updater = self.savings_map.setter(name_of_saving.clone());
+        saving_updater.goal_reached.set(true);
+
+        evm::log(SavingGoalReached {
+            user: self.user_address.get(),
+            name_of_saving,
+            goal_amount,
+        });
+    }
+
+    /// Append a name to the active or closed names list
+    fn push_name(&mut self, name_of_saving: String, closed: bool) {
+        let names = if closed {
+            &mut self.closed_savings_names
+        } else {
+            &mut self.savings_names
+        };
+
+        names.grow().set_str(name_of_saving.clone());
+        let name_index = U256::from(names.len());
+        self.savings_map.setter(name_of_saving).name_index.set(name_index);
+    }
+
+    /// Swap remove a name from the active or closed names list, if it's there
+    fn remove_name(&mut self, name_of_saving: String, closed: bool) {
+        let name_index = self.savings_map.get(name_of_saving.clone()).name_index.get();
+        if name_index == U256::ZERO {
+            return;
+        }
+
+        let names = if closed {
+            &mut self.closed_savings_names
+        } else {
+            &mut self.savings_names
+        };
+
+        let position = name_index.to::<usize>() - 1;
+        let last_position = names.len() - 1;
+        if position != last_position {
+            // move the last name into the freed slot
+            let last_name = names
+                .get(last_position)
+                .map(|name| name.get_string())
+                .unwrap_or_default();
+            if let Some(mut slot) = names.setter(position) {
+                slot.set_str(last_name.clone());
+            }
+            self.savings_map.setter(last_name).name_index.set(name_index);
+        }
+        names.erase_last();
+        self.savings_map.setter(name_of_saving).name_index.set(U256::ZERO);
+    }
+
+    /// A page of the active or closed saving names, with the list's total length
+    pub fn saving_names(&self, closed: bool, offset: U256, limit: U256) -> (Vec<String>, U256) {
+        let names = if closed {
+            &self.closed_savings_names
+        } else {
+            &self.savings_names
+        };
+
+        let total = U256::from(names.len());
+        let start = offset.min(total).to::<usize>();
+        let end = offset.saturating_add(limit).min(total).to::<usize>();
+
+        let mut page = Vec::with_capacity(end - start);
+        for i in start..end {
+            if let Some(name) = names.get(i) {
+                page.push(name.get_string());
+            }
+        }
+        (page, total)
+    }
+
+    /// Add to the user's points
+    fn credit_points(&mut self, points: U256) {
+        let previous_points = self.total_point.get();
+        self.set_points(previous_points, previous_points + points);
+    }
+
+    /// Hold a saving's points until it completes
+    fn lock_points(&mut self, points: U256) {
+        self.locked_points.set(self.locked_points.get() + points);
+    }
+
+    /// Release a closed saving's locked points, credited if the saving earned them
+    fn release_points(&mut self, points: U256, earned: bool) {
+        self.locked_points
+            .set(self.locked_points.get().saturating_sub(points));
+        if earned {
+            self.credit_points(points);
+        }
+    }
+
+    fn set_points(&mut self, previous_points: U256, new_points: U256) {
+        if previous_points == new_points {
+            return;
+        }
+
+        self.total_point.set(new_points);
+        evm::log(PointsUpdated {
+            user: self.user_address.get(),
+            previous_points,
+            new_points,
+        });
+    }
+
+    /// Spend points on a redemption, errors if the user doesn't have enough
+    pub fn spend_points(&mut self, points: U256) -> BResult<()> {
+        let previous_points = self.total_point.get();
+        if points == U256::ZERO || points > previous_points {
+            return Err(BitsaveErrors::InsufficientPoints(InsufficientPoints {}));
+        }
+
+        self.set_points(previous_points, previous_points - points);
+        Ok(())
+    }
+
+    /// Checkpoint a saving's penalty pool share up to `pool_index`;
+    /// an empty saving, new or reusing a closed name, only takes the index
+    pub fn accrue_pool_share(&mut self, name_of_saving: String, pool_index: U256) {
+        let saving_data = self.savings_map.get(name_of_saving.clone());
+        let amount = saving_data.amount.get();
+        let pool_reward = if amount == U256::ZERO {
+            saving_data.pool_reward.get()
+        } else {
+            let accrued = amount * (pool_index - saving_data.pool_index.get()) / U256::from(WAD);
+            saving_data.pool_reward.get() + accrued
+        };
+
+        let mut saving_updater = self.savings_map.setter(name_of_saving);
+        saving_updater.pool_reward.set(pool_reward);
+        saving_updater.pool_index.set(pool_index);
+    }
+
+    /// Zero a withdrawn saving's interest once paid,
+    /// any unpaid part is kept as pending interest
+    pub fn settle_interest(&mut self, name_of_saving: String, interest_paid: U256) {
+        let interest = self.savings_map.get(name_of_saving.clone()).interest_accumulated.get();
+        let unpaid = interest.saturating_sub(interest_paid);
+
+        let mut saving_updater = self.savings_map.setter(name_of_saving);
+        saving_updater.interest_accumulated.set(U256::from(0));
 
-        Ok(withdraw_amount)
+        self.pending_interest.set(self.pending_interest.get() + unpaid);
     }
 }